@@ -1,51 +1,85 @@
-const {
-    Connection,
-    PublicKey,
-    Keypair,
-    SystemProgram,
-    Transaction,
-    TransactionInstruction,
-    sendAndConfirmTransaction,
-  } = require('@solana/web3.js');
+const anchor = require('@coral-xyz/anchor');
+  const { getAssociatedTokenAddressSync } = require('@solana/spl-token');
+  const { Connection, PublicKey, Keypair } = require('@solana/web3.js');
   const fs = require('fs');
-  
-  const PROGRAM_ID = 'your_program_id_here'; // We'll update this after deploying
-  
+  const path = require('path');
+
+  // Built by `anchor build` in test-liquidation; it also carries the program id
+  const IDL_PATH = path.resolve(__dirname, '../../../test-liquidation/target/idl/test_liquidation.json');
+  const LENDING_MARKET = 'your_lending_market_here';
+  const COLLATERAL_RESERVE = 'your_collateral_reserve_here';
+  const BORROW_RESERVE = 'your_borrow_reserve_here';
+
   async function createPosition() {
     // Connect to devnet
     const connection = new Connection('https://api.devnet.solana.com', 'confirmed');
-    
-    // Load your wallet from existing wallet.json
+
+    // Load your wallet from existing wallet.json; it must own the lending market
     const wallet = Keypair.fromSecretKey(
       Buffer.from(JSON.parse(fs.readFileSync('../../../wallet.json', 'utf-8')))
     );
-  
-    // Create account for position
-    const positionAccount = Keypair.generate();
-    
-    const createPositionIx = new TransactionInstruction({
-      programId: new PublicKey(PROGRAM_ID),
-      keys: [
-        { pubkey: positionAccount.publicKey, isSigner: true, isWritable: true },
-        { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
-      ],
-      data: Buffer.from([0]) // CreatePosition instruction
+
+    const provider = new anchor.AnchorProvider(connection, new anchor.Wallet(wallet), {
+      commitment: 'confirmed',
     });
-  
-    const tx = new Transaction().add(createPositionIx);
-    
+    const program = new anchor.Program(JSON.parse(fs.readFileSync(IDL_PATH, 'utf-8')), provider);
+    const lendingMarket = new PublicKey(LENDING_MARKET);
+    const collateralReserve = new PublicKey(COLLATERAL_RESERVE);
+    const borrowReserve = new PublicKey(BORROW_RESERVE);
+    const collateral = await program.account.reserve.fetch(collateralReserve);
+    const borrow = await program.account.reserve.fetch(borrowReserve);
+
+    // Positions are PDAs of (market, owner, index); the counter holds the next index
+    const [counter] = PublicKey.findProgramAddressSync(
+      [Buffer.from('obligation_counter'), lendingMarket.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const counterState = await program.account.obligationCounter.fetchNullable(counter);
+    const index = counterState ? counterState.nextIndex : new anchor.BN(0);
+    const [positionAccount] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('obligation'),
+        lendingMarket.toBuffer(),
+        wallet.publicKey.toBuffer(),
+        index.toArrayLike(Buffer, 'le', 8),
+      ],
+      program.programId
+    );
+
+    // Each reserve's configured oracles, collateral reserve first
+    const oracleMetas = [collateral, borrow].flatMap((reserve) =>
+      reserve.liquidity.oracles
+        .filter((oracle) => !oracle.pubkey.equals(PublicKey.default))
+        .map((oracle) => ({ pubkey: oracle.pubkey, isSigner: false, isWritable: false }))
+    );
+
     try {
-      const txid = await sendAndConfirmTransaction(
-        connection,
-        tx,
-        [wallet, positionAccount]
-      );
-      
+      const txid = await program.methods
+        .createRiskyPosition()
+        .accountsPartial({
+          position: {
+            lendingMarket,
+            obligationCounter: counter,
+            obligation: positionAccount,
+            collateralReserve,
+            collateralSupply: collateral.collateral.supplyPubkey,
+            borrowReserve,
+            liquiditySupply: borrow.liquidity.supplyPubkey,
+            userCollateral: getAssociatedTokenAddressSync(collateral.liquidity.mintPubkey, wallet.publicKey),
+            userDebt: getAssociatedTokenAddressSync(borrow.liquidity.mintPubkey, wallet.publicKey),
+            user: wallet.publicKey,
+          },
+          owner: wallet.publicKey,
+        })
+        .remainingAccounts(oracleMetas)
+        .rpc();
+
       console.log('Created position:', txid);
-      console.log('Position account:', positionAccount.publicKey.toString());
+      console.log('Position account:', positionAccount.toString());
+      console.log('Position index:', index.toString());
     } catch (err) {
       console.error('Error:', err);
     }
   }
-  
-  createPosition().catch(console.error);
\ No newline at end of file
+
+  createPosition().catch(console.error);
@@ -0,0 +1,124 @@
+// The IDL instructions generated by `#[program]` still call `AccountInfo::realloc`.
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("5WVgPSfSYmGadpdsiisTj35vqpnQT9MAK1toeYT75Di4");
+
+/// Price feeds whose authority can move them at will, so tests can crash a price
+/// and watch liquidations trigger.
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    pub fn init_price_feed(
+        ctx: Context<InitPriceFeed>,
+        price: i64,
+        expo: i32,
+        conf: u64,
+    ) -> Result<()> {
+        require!(price > 0, MockOracleError::InvalidPrice);
+
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.authority = ctx.accounts.authority.key();
+        price_feed.expo = expo;
+        price_feed.publish(price, conf)
+    }
+
+    pub fn set_price(ctx: Context<SetPrice>, price: i64, conf: u64) -> Result<()> {
+        require!(price > 0, MockOracleError::InvalidPrice);
+
+        ctx.accounts.price_feed.publish(price, conf)
+    }
+
+    /// Updates every price feed passed as a writable remaining account, in order.
+    pub fn set_price_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetPriceBatch<'info>>,
+        updates: Vec<PriceUpdate>,
+    ) -> Result<()> {
+        require!(
+            updates.len() == ctx.remaining_accounts.len(),
+            MockOracleError::BatchLengthMismatch
+        );
+
+        for (info, update) in ctx.remaining_accounts.iter().zip(updates) {
+            require!(update.price > 0, MockOracleError::InvalidPrice);
+
+            let mut price_feed = Account::<PriceFeed>::try_from(info)?;
+            require_keys_eq!(
+                price_feed.authority,
+                ctx.accounts.authority.key(),
+                MockOracleError::Unauthorized
+            );
+            price_feed.publish(update.price, update.conf)?;
+            price_feed.exit(ctx.program_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitPriceFeed<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PriceFeed::INIT_SPACE
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    #[account(mut, has_one = authority @ MockOracleError::Unauthorized)]
+    pub price_feed: Account<'info, PriceFeed>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceBatch<'info> {
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PriceUpdate {
+    pub price: i64,
+    pub conf: u64,
+}
+
+/// The price of one whole token in USD is `price * 10^expo`, give or take `conf * 10^expo`.
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub conf: u64,
+    pub publish_slot: u64,
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    fn publish(&mut self, price: i64, conf: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        self.price = price;
+        self.conf = conf;
+        self.publish_slot = clock.slot;
+        self.publish_time = clock.unix_timestamp;
+
+        Ok(())
+    }
+}
+
+#[error_code]
+pub enum MockOracleError {
+    #[msg("Price must be positive")]
+    InvalidPrice,
+    #[msg("Signer is not the price feed authority")]
+    Unauthorized,
+    #[msg("Number of updates does not match the number of price feeds")]
+    BatchLengthMismatch,
+}
@@ -0,0 +1,53 @@
+/// Seed for the PDA that signs for every token vault of a lending market.
+pub const LENDING_MARKET_AUTHORITY_SEED: &[u8] = b"authority";
+
+/// Seed for a reserve, derived per lending market and liquidity mint.
+pub const RESERVE_SEED: &[u8] = b"reserve";
+
+/// Seed for an obligation, derived per lending market, owner and position index.
+pub const OBLIGATION_SEED: &[u8] = b"obligation";
+
+/// Seed for the counter handing out position indexes, derived per lending market and owner.
+pub const OBLIGATION_COUNTER_SEED: &[u8] = b"obligation_counter";
+
+/// Seed for a risk parameter proposal, derived per lending market and proposal id.
+pub const RISK_PROPOSAL_SEED: &[u8] = b"risk_proposal";
+
+/// Seed for the reserve vault holding liquidity available to borrowers.
+pub const LIQUIDITY_SUPPLY_SEED: &[u8] = b"liquidity_supply";
+
+/// Seed for the reserve vault escrowing collateral deposited by positions.
+pub const COLLATERAL_SUPPLY_SEED: &[u8] = b"collateral_supply";
+
+/// Seed for the reserve vault collecting the protocol's share of liquidation bonuses.
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Seed for the reserve vault whose funds cover bad debt before it is socialized.
+pub const INSURANCE_VAULT_SEED: &[u8] = b"insurance_vault";
+
+/// Seed for a lending market's insurance fund.
+pub const INSURANCE_FUND_SEED: &[u8] = b"insurance_fund";
+
+/// Seed for a staker's insurance stake, derived per reserve and staker.
+pub const INSURANCE_STAKE_SEED: &[u8] = b"insurance_stake";
+
+/// Scale of the fixed-point `*_wads` values, matching Solend.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+pub const PROGRAM_VERSION: u8 = 1;
+
+/// Slots per year at roughly two slots a second, used to turn annual rates into
+/// per-slot rates.
+pub const SLOTS_PER_YEAR: u64 = 63_072_000;
+
+/// Maximum number of price feeds a reserve can reference.
+pub const MAX_RESERVE_ORACLES: usize = 3;
+
+/// Percent of an emptied obligation's rent paid to whoever closes it on the owner's behalf.
+pub const CLOSE_EMPTY_OBLIGATION_REWARD_PERCENT: u64 = 5;
+
+/// Maximum number of deposit legs, and separately of borrow legs, in one obligation.
+pub const MAX_OBLIGATION_LEGS: usize = 5;
+
+/// Maximum number of signers in a lending market's governance set.
+pub const MAX_GOVERNANCE_SIGNERS: usize = 5;
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum LiquidationError {
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("Obligation is not liquidatable")]
+    NotLiquidatable,
+    #[msg("Repay amount exceeds the close factor of the borrow")]
+    LiquidationTooLarge,
+    #[msg("Repay amount is too small to seize any collateral")]
+    LiquidationTooSmall,
+    #[msg("Reserve does not have enough available liquidity")]
+    InsufficientLiquidity,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Reserve config is invalid")]
+    InvalidConfig,
+    #[msg("Borrow would exceed the obligation's allowed borrow value")]
+    BorrowTooLarge,
+    #[msg("Withdraw amount exceeds the obligation's deposit")]
+    InsufficientCollateral,
+    #[msg("Withdraw would leave the obligation above its allowed borrow value")]
+    WithdrawTooLarge,
+    #[msg("Collateral and borrow reserves must differ")]
+    DuplicateReserve,
+    #[msg("Obligation has no free deposit or borrow slot")]
+    ObligationLegsFull,
+    #[msg("Obligation has no deposit or borrow in this reserve")]
+    ObligationLegNotFound,
+    #[msg("A reserve referenced by the obligation was not passed in")]
+    ObligationReserveMissing,
+    #[msg("Obligation must have no deposits or borrows left and have been liquidated")]
+    ObligationNotEmpty,
+    #[msg("Oracle account does not match the reserve")]
+    InvalidOracle,
+    #[msg("Oracle price must be positive")]
+    InvalidOraclePrice,
+    #[msg("Reserve must be refreshed in the current slot")]
+    ReserveStale,
+    #[msg("Obligation must be refreshed in the current slot")]
+    ObligationStale,
+    #[msg("Reserve cumulative borrow rate decreased")]
+    NegativeInterestRate,
+    #[msg("Oracle feed id does not match the reserve")]
+    InvalidOracleFeedId,
+    #[msg("Oracle price is stale")]
+    StaleOracle,
+    #[msg("Oracle confidence interval is too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Oracle price moved too far from the last accepted price")]
+    OraclePriceDeviation,
+    #[msg("Math operation overflowed")]
+    MathOverflow,
+    #[msg("Flash loans must be called directly, not through CPI")]
+    FlashLoanCpi,
+    #[msg("Flash borrow has no matching flash repay later in the transaction")]
+    FlashRepayMissing,
+    #[msg("Another flash borrow comes before this flash borrow is repaid")]
+    MultipleFlashBorrows,
+    #[msg("Flash repay does not match its flash borrow")]
+    InvalidFlashRepay,
+    #[msg("Obligation still holds collateral, so its debt is not bad debt")]
+    NoBadDebt,
+    #[msg("Insurance vault was emptied by bad debt and cannot take new stakes")]
+    InsuranceVaultDepleted,
+    #[msg("Not enough insurance shares")]
+    InsufficientInsuranceShares,
+    #[msg("Insurance unstake cooldown has not elapsed")]
+    InsuranceCooldownActive,
+    #[msg("Lending market is paused")]
+    MarketPaused,
+    #[msg("Borrowing is paused")]
+    BorrowsPaused,
+    #[msg("Withdrawals are paused")]
+    WithdrawalsPaused,
+    #[msg("Liquidations are paused")]
+    LiquidationsPaused,
+    #[msg("Borrowing is halted by the oracle circuit breaker")]
+    CircuitBreakerTripped,
+    #[msg("Risk parameters can only be changed through a governance proposal")]
+    RiskParametersGoverned,
+    #[msg("Governance is already set up for this market")]
+    GovernanceAlreadyInitialized,
+    #[msg("Proposal does not have enough approvals")]
+    ProposalNotApproved,
+    #[msg("Proposal timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Proposal was already executed")]
+    ProposalAlreadyExecuted,
+}
@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+
+use crate::oracle::PriceSource;
+use crate::state::{MarketPauses, RiskParameters};
+
+/// A reserve accepted a new oracle price.
+#[event]
+pub struct ReservePriceUpdated {
+    pub reserve: Pubkey,
+    pub source: PriceSource,
+    /// Accepted price of one whole token in USD, scaled by `WAD`.
+    pub market_price: u128,
+}
+
+/// A liquidator repaid part of an obligation's borrow and seized its collateral.
+#[event]
+pub struct ObligationLiquidated {
+    pub obligation: Pubkey,
+    pub liquidator: Pubkey,
+    pub repay_reserve: Pubkey,
+    pub withdraw_reserve: Pubkey,
+    pub repaid_amount: u64,
+    /// Collateral taken from the obligation, bonus included.
+    pub seized_amount: u64,
+    /// Part of `seized_amount` that is liquidation bonus.
+    pub bonus_amount: u64,
+    /// Part of the bonus kept by the protocol rather than paid to the liquidator.
+    pub protocol_fee: u64,
+    /// Part of `protocol_fee` paid into the insurance vault rather than the treasury.
+    pub insurance_fee: u64,
+    /// Health factor, scaled by `WAD`, before the liquidation.
+    pub health_factor_before: u128,
+    /// Health factor, scaled by `WAD`, from the obligation's cached values after the
+    /// liquidation.
+    pub health_factor_after: u128,
+}
+
+/// An owner opened a new obligation.
+#[event]
+pub struct PositionOpened {
+    pub obligation: Pubkey,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    pub index: u64,
+}
+
+/// Collateral was deposited into an obligation.
+#[event]
+pub struct CollateralDeposited {
+    pub obligation: Pubkey,
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// Liquidity was borrowed against an obligation.
+#[event]
+pub struct LiquidityBorrowed {
+    pub obligation: Pubkey,
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// Part of an obligation's borrow was repaid, not counting liquidations.
+#[event]
+pub struct LiquidityRepaid {
+    pub obligation: Pubkey,
+    pub reserve: Pubkey,
+    pub repayer: Pubkey,
+    pub amount: u64,
+}
+
+/// Collateral was withdrawn from an obligation, not counting liquidations.
+#[event]
+pub struct CollateralWithdrawn {
+    pub obligation: Pubkey,
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// An obligation was closed and its rent refunded.
+#[event]
+pub struct PositionClosed {
+    pub obligation: Pubkey,
+    pub owner: Pubkey,
+    /// Signer that closed it: the owner, or whoever cranked an emptied obligation.
+    pub closed_by: Pubkey,
+    /// Lamports of rent paid to `closed_by` rather than the owner.
+    pub reward: u64,
+}
+
+/// Reserve liquidity was flash borrowed, to be repaid later in the transaction.
+#[event]
+pub struct FlashBorrowed {
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// A flash loan was repaid with its fee.
+#[event]
+pub struct FlashRepaid {
+    pub reserve: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+/// Debt left on an obligation with no collateral was written off.
+#[event]
+pub struct BadDebtWrittenOff {
+    pub obligation: Pubkey,
+    pub reserve: Pubkey,
+    pub bad_debt: u64,
+    /// Part of `bad_debt` paid from the reserve's insurance vault.
+    pub insurance_covered: u64,
+    /// Part of `bad_debt` the vault did not cover, borne by the reserve's suppliers
+    /// once it has cancelled any interest still owed to the insurance fund.
+    pub socialized: u64,
+    /// Liquidity per supply share after the write-off, scaled by `WAD`.
+    pub supply_exchange_rate: u128,
+}
+
+/// A staker added funds to a reserve's insurance vault.
+#[event]
+pub struct InsuranceStaked {
+    pub reserve: Pubkey,
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+}
+
+/// A staker withdrew insurance shares after their cooldown.
+#[event]
+pub struct InsuranceUnstaked {
+    pub reserve: Pubkey,
+    pub staker: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+}
+
+/// The market owner changed which operations are paused.
+#[event]
+pub struct MarketPausesUpdated {
+    pub lending_market: Pubkey,
+    pub pauses: MarketPauses,
+}
+
+/// A reserve's price moved far enough within the breaker window to halt borrowing.
+#[event]
+pub struct CircuitBreakerTripped {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    /// Price the window opened at, scaled by `WAD`.
+    pub reference_price: u128,
+    pub market_price: u128,
+}
+
+/// A governance signer proposed new risk parameters for a reserve.
+#[event]
+pub struct RiskProposalCreated {
+    pub proposal: Pubkey,
+    pub reserve: Pubkey,
+    pub proposer: Pubkey,
+    pub risk_parameters: RiskParameters,
+}
+
+/// A governance signer approved a risk proposal.
+#[event]
+pub struct RiskProposalApproved {
+    pub proposal: Pubkey,
+    pub signer: Pubkey,
+    /// Slot the timelock started at, once the approval threshold is reached.
+    pub approved_slot: Option<u64>,
+}
+
+/// A risk proposal was executed after its timelock, changing the reserve's risk
+/// parameters.
+#[event]
+pub struct RiskProposalExecuted {
+    pub proposal: Pubkey,
+    pub reserve: Pubkey,
+    pub risk_parameters: RiskParameters,
+}
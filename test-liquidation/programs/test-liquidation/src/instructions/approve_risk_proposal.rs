@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RISK_PROPOSAL_SEED;
+use crate::error::LiquidationError;
+use crate::events::RiskProposalApproved;
+use crate::state::{LendingMarket, RiskProposal};
+
+#[derive(Accounts)]
+pub struct ApproveRiskProposal<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(
+        mut,
+        has_one = lending_market,
+        seeds = [
+            RISK_PROPOSAL_SEED,
+            proposal.lending_market.as_ref(),
+            &proposal.id.to_le_bytes(),
+        ],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, RiskProposal>,
+    pub signer: Signer<'info>,
+}
+
+pub fn process_approve_risk_proposal(ctx: Context<ApproveRiskProposal>) -> Result<()> {
+    let governance = &ctx.accounts.lending_market.governance;
+    require!(
+        governance.is_signer(&ctx.accounts.signer.key()),
+        LiquidationError::Unauthorized
+    );
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        !proposal.executed,
+        LiquidationError::ProposalAlreadyExecuted
+    );
+
+    proposal.approve(
+        ctx.accounts.signer.key(),
+        governance.threshold,
+        Clock::get()?.slot,
+    );
+
+    emit!(RiskProposalApproved {
+        proposal: proposal.key(),
+        signer: ctx.accounts.signer.key(),
+        approved_slot: proposal.approved_slot,
+    });
+
+    Ok(())
+}
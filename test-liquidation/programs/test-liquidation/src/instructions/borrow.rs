@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::LiquidityBorrowed;
+use crate::math::Decimal;
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct Borrow<'info> {
+    #[account(
+        mut,
+        has_one = owner @ LiquidationError::Unauthorized,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub borrow_reserve: Account<'info, Reserve>,
+    #[account(mut, address = borrow_reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = borrow_reserve.liquidity.mint_pubkey,
+    )]
+    pub destination_debt: Account<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Borrows against a refreshed obligation, up to the borrow value its deposits
+/// still support.
+pub fn process_borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_borrows_allowed()?;
+
+    let slot = Clock::get()?.slot;
+    let borrow_reserve = &mut ctx.accounts.borrow_reserve;
+    let obligation = &mut ctx.accounts.obligation;
+    require!(
+        !borrow_reserve.last_update.is_stale(slot),
+        LiquidationError::ReserveStale
+    );
+    require!(
+        !obligation.last_update.is_stale(slot),
+        LiquidationError::ObligationStale
+    );
+    require!(
+        borrow_reserve.market_value(Decimal::from(amount))? <= obligation.remaining_borrow_value(),
+        LiquidationError::BorrowTooLarge
+    );
+
+    obligation.borrow(
+        borrow_reserve.key(),
+        amount,
+        borrow_reserve.liquidity.cumulative_borrow_rate_wads,
+    )?;
+    obligation.last_update.mark_stale();
+
+    borrow_reserve.liquidity.borrow(amount)?;
+    borrow_reserve.last_update.mark_stale();
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.destination_debt,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        amount,
+    )?;
+
+    emit!(LiquidityBorrowed {
+        obligation: ctx.accounts.obligation.key(),
+        reserve: ctx.accounts.borrow_reserve.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CLOSE_EMPTY_OBLIGATION_REWARD_PERCENT, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::PositionClosed;
+use crate::state::Obligation;
+
+#[derive(Accounts)]
+pub struct CloseEmptyPosition<'info> {
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+        close = owner,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    /// CHECK: Receives the rest of the rent; checked against the obligation owner.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub caller: Signer<'info>,
+}
+
+/// Permissionless crank closing an obligation that liquidations left with neither
+/// deposits nor borrows. The caller keeps `CLOSE_EMPTY_OBLIGATION_REWARD_PERCENT` of
+/// the rent and the owner gets the rest back. Returns the caller's share in lamports.
+pub fn process_close_empty_position(ctx: Context<CloseEmptyPosition>) -> Result<u64> {
+    let obligation = &ctx.accounts.obligation;
+    require!(
+        obligation.liquidated && obligation.deposits.is_empty() && obligation.borrows.is_empty(),
+        LiquidationError::ObligationNotEmpty
+    );
+
+    let obligation_info = obligation.to_account_info();
+    let reward = obligation_info.lamports() * CLOSE_EMPTY_OBLIGATION_REWARD_PERCENT / 100;
+    obligation_info.sub_lamports(reward)?;
+    ctx.accounts.caller.add_lamports(reward)?;
+
+    emit!(PositionClosed {
+        obligation: obligation.key(),
+        owner: obligation.owner,
+        closed_by: ctx.accounts.caller.key(),
+        reward,
+    });
+
+    Ok(reward)
+}
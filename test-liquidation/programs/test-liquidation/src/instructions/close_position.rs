@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::{CollateralWithdrawn, LiquidityRepaid, PositionClosed};
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::{transfer_from_vault, transfer_to_vault};
+
+/// Remaining accounts: for every borrow leg, in order, its reserve, the reserve's
+/// liquidity supply and the owner's token account repaying it; then for every deposit
+/// leg, in order, its reserve, the reserve's collateral supply and the token account
+/// receiving the collateral.
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        mut,
+        has_one = owner @ LiquidationError::Unauthorized,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+        close = owner,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Repays every outstanding borrow in full, returns all deposited collateral and closes
+/// the obligation, refunding its rent to the owner. No refresh is needed since nothing
+/// is left to value once the position is empty.
+pub fn process_close_position<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClosePosition<'info>>,
+) -> Result<()> {
+    ctx.accounts.lending_market.check_withdrawals_allowed()?;
+    let obligation = &ctx.accounts.obligation;
+    require!(
+        ctx.remaining_accounts.len() == 3 * (obligation.borrows.len() + obligation.deposits.len()),
+        LiquidationError::ObligationReserveMissing
+    );
+    let (borrow_accounts, deposit_accounts) = ctx
+        .remaining_accounts
+        .split_at(3 * obligation.borrows.len());
+
+    let slot = Clock::get()?.slot;
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let borrows = obligation.borrows.clone();
+    for (leg, accounts) in borrows.iter().zip(borrow_accounts.chunks(3)) {
+        let mut reserve = load_leg_reserve(&accounts[0], leg.borrow_reserve, lending_market_key)?;
+        let liquidity_supply = Account::<TokenAccount>::try_from(&accounts[1])?;
+        let source = Account::<TokenAccount>::try_from(&accounts[2])?;
+        require_keys_eq!(
+            liquidity_supply.key(),
+            reserve.liquidity.supply_pubkey,
+            ErrorCode::ConstraintAddress
+        );
+        require_keys_eq!(
+            source.owner,
+            ctx.accounts.owner.key(),
+            ErrorCode::ConstraintTokenOwner
+        );
+
+        reserve.accrue_interest(slot)?;
+        let repay_amount = ctx.accounts.obligation.repay(
+            leg.borrow_reserve,
+            u64::MAX,
+            reserve.liquidity.cumulative_borrow_rate_wads,
+        )?;
+        reserve.liquidity.repay(repay_amount)?;
+        reserve.last_update.mark_stale();
+        reserve.exit(&crate::ID)?;
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &source,
+            &liquidity_supply,
+            &ctx.accounts.owner,
+            repay_amount,
+        )?;
+        emit!(LiquidityRepaid {
+            obligation: ctx.accounts.obligation.key(),
+            reserve: leg.borrow_reserve,
+            repayer: ctx.accounts.owner.key(),
+            amount: repay_amount,
+        });
+    }
+
+    let deposits = ctx.accounts.obligation.deposits.clone();
+    for (leg, accounts) in deposits.iter().zip(deposit_accounts.chunks(3)) {
+        let mut reserve = load_leg_reserve(&accounts[0], leg.deposit_reserve, lending_market_key)?;
+        let collateral_supply = Account::<TokenAccount>::try_from(&accounts[1])?;
+        let destination = Account::<TokenAccount>::try_from(&accounts[2])?;
+        require_keys_eq!(
+            collateral_supply.key(),
+            reserve.collateral.supply_pubkey,
+            ErrorCode::ConstraintAddress
+        );
+        require_keys_eq!(
+            destination.mint,
+            reserve.liquidity.mint_pubkey,
+            ErrorCode::ConstraintTokenMint
+        );
+
+        ctx.accounts
+            .obligation
+            .withdraw(leg.deposit_reserve, leg.deposited_amount)?;
+        reserve.collateral.deposited_amount = reserve
+            .collateral
+            .deposited_amount
+            .checked_sub(leg.deposited_amount)
+            .ok_or(LiquidationError::MathOverflow)?;
+        reserve.exit(&crate::ID)?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &collateral_supply,
+            &destination,
+            &ctx.accounts.lending_market,
+            &ctx.accounts.lending_market_authority,
+            leg.deposited_amount,
+        )?;
+        emit!(CollateralWithdrawn {
+            obligation: ctx.accounts.obligation.key(),
+            reserve: leg.deposit_reserve,
+            amount: leg.deposited_amount,
+        });
+    }
+
+    emit!(PositionClosed {
+        obligation: ctx.accounts.obligation.key(),
+        owner: ctx.accounts.owner.key(),
+        closed_by: ctx.accounts.owner.key(),
+        reward: 0,
+    });
+
+    Ok(())
+}
+
+/// Loads the writable reserve backing an obligation leg from the remaining accounts.
+fn load_leg_reserve<'info>(
+    info: &'info AccountInfo<'info>,
+    leg_reserve: Pubkey,
+    lending_market: Pubkey,
+) -> Result<Account<'info, Reserve>> {
+    require_keys_eq!(
+        info.key(),
+        leg_reserve,
+        LiquidationError::ObligationReserveMissing
+    );
+    require!(info.is_writable, ErrorCode::ConstraintMut);
+    let reserve = Account::<Reserve>::try_from(info)?;
+    require_keys_eq!(
+        reserve.lending_market,
+        lending_market,
+        ErrorCode::ConstraintHasOne
+    );
+    Ok(reserve)
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::LENDING_MARKET_AUTHORITY_SEED;
+use crate::state::{LendingMarket, Reserve};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct CollectInsuranceFees<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(mut, address = reserve.insurance.vault_pubkey)]
+    pub insurance_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Moves the insurance fund's share of accrued interest from the reserve's liquidity
+/// into its insurance vault, as far as unborrowed liquidity allows. Anyone may call
+/// it. Returns the amount moved.
+pub fn process_collect_insurance_fees(ctx: Context<CollectInsuranceFees>) -> Result<u64> {
+    ctx.accounts.lending_market.check_not_paused()?;
+    let reserve = &mut ctx.accounts.reserve;
+    reserve.accrue_interest(Clock::get()?.slot)?;
+    let amount = reserve.liquidity.collect_insurance_fees()?;
+    if amount > 0 {
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.liquidity_supply,
+            &ctx.accounts.insurance_vault,
+            &ctx.accounts.lending_market,
+            &ctx.accounts.lending_market_authority,
+            amount,
+        )?;
+    }
+
+    Ok(amount)
+}
@@ -0,0 +1,254 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, OBLIGATION_COUNTER_SEED, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::{CollateralDeposited, LiquidityBorrowed, PositionOpened};
+use crate::oracle::get_market_price;
+use crate::state::{LendingMarket, Obligation, ObligationCounter, Reserve};
+use crate::utils::{transfer_from_vault, transfer_to_vault};
+
+use super::refresh_reserve::track_price_move;
+
+/// Remaining accounts: the collateral reserve's oracles followed by the borrow
+/// reserve's oracles, each in configured order.
+#[derive(Accounts)]
+pub struct CreatePosition<'info> {
+    #[account(mut)]
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + ObligationCounter::INIT_SPACE,
+        seeds = [OBLIGATION_COUNTER_SEED, lending_market.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub obligation_counter: Account<'info, ObligationCounter>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Obligation::INIT_SPACE,
+        seeds = [
+            OBLIGATION_SEED,
+            lending_market.key().as_ref(),
+            user.key().as_ref(),
+            &obligation_counter.next_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub collateral_reserve: Account<'info, Reserve>,
+    #[account(mut, address = collateral_reserve.collateral.supply_pubkey)]
+    pub collateral_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = lending_market,
+        constraint = borrow_reserve.key() != collateral_reserve.key() @ LiquidationError::DuplicateReserve,
+    )]
+    pub borrow_reserve: Account<'info, Reserve>,
+    #[account(mut, address = borrow_reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = collateral_reserve.liquidity.mint_pubkey,
+        token::authority = user,
+    )]
+    pub user_collateral: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = borrow_reserve.liquidity.mint_pubkey,
+    )]
+    pub user_debt: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remaining accounts: as for `CreatePosition`.
+#[derive(Accounts)]
+pub struct CreateRiskyPosition<'info> {
+    pub position: CreatePosition<'info>,
+    #[account(
+        constraint = position.lending_market.owner == owner.key() @ LiquidationError::Unauthorized,
+    )]
+    pub owner: Signer<'info>,
+}
+
+/// Opens an obligation with one deposit and, optionally, one borrow.
+pub fn process_create_position(
+    ctx: Context<CreatePosition>,
+    collateral_amount: u64,
+    borrow_amount: u64,
+) -> Result<()> {
+    require!(collateral_amount > 0, LiquidationError::InvalidAmount);
+
+    open_position(
+        ctx.accounts,
+        &ctx.bumps,
+        ctx.remaining_accounts,
+        collateral_amount,
+        borrow_amount,
+        true,
+    )
+}
+
+/// Opens an obligation that is underwater from the start, skipping the borrow limit check.
+/// Kept for tooling that just needs something to liquidate, so only the market owner
+/// may call it.
+pub fn process_create_risky_position(ctx: Context<CreateRiskyPosition>) -> Result<()> {
+    // Deposit 0.1 SOL but borrow 0.2 SOL worth of value
+    let collateral_amount = 100_000_000; // 0.1 SOL
+    let borrowed_amount = 200_000_000; // 0.2 SOL equivalent
+
+    open_position(
+        &mut ctx.accounts.position,
+        &ctx.bumps.position,
+        ctx.remaining_accounts,
+        collateral_amount,
+        borrowed_amount,
+        false,
+    )
+}
+
+fn open_position<'info>(
+    accounts: &mut CreatePosition<'info>,
+    bumps: &CreatePositionBumps,
+    oracles: &[AccountInfo],
+    collateral_amount: u64,
+    borrow_amount: u64,
+    check_borrow_limit: bool,
+) -> Result<()> {
+    if borrow_amount > 0 {
+        accounts.lending_market.check_borrows_allowed()?;
+    } else {
+        accounts.lending_market.check_not_paused()?;
+    }
+    let collateral_reserve_key = accounts.collateral_reserve.key();
+    let borrow_reserve_key = accounts.borrow_reserve.key();
+    let clock = Clock::get()?;
+    accounts.collateral_reserve.accrue_interest(clock.slot)?;
+    accounts.borrow_reserve.accrue_interest(clock.slot)?;
+    let collateral_oracle_count = accounts.collateral_reserve.liquidity.oracles().len();
+    require!(
+        oracles.len() >= collateral_oracle_count,
+        LiquidationError::InvalidOracle
+    );
+    let (collateral_oracles, borrow_oracles) = oracles.split_at(collateral_oracle_count);
+    let collateral_price = get_market_price(
+        collateral_reserve_key,
+        &accounts.collateral_reserve,
+        collateral_oracles,
+    )?;
+    let borrow_price =
+        get_market_price(borrow_reserve_key, &accounts.borrow_reserve, borrow_oracles)?;
+    for (reserve, market_price) in [
+        (&mut accounts.collateral_reserve, collateral_price),
+        (&mut accounts.borrow_reserve, borrow_price),
+    ] {
+        reserve.liquidity.market_price = market_price;
+        reserve.liquidity.market_price_slot = clock.slot;
+        reserve.last_update.update_slot(clock.slot);
+        track_price_move(
+            &mut accounts.lending_market,
+            reserve,
+            market_price,
+            clock.slot,
+        )?;
+    }
+    // A trip on these prices fails the borrow, discarding the trip with it, rather
+    // than lending at a price the breaker rejects
+    if borrow_amount > 0 {
+        accounts.lending_market.check_borrows_allowed()?;
+    }
+
+    let lending_market = accounts.lending_market.key();
+    let owner = accounts.user.key();
+    let counter = &mut accounts.obligation_counter;
+    counter.lending_market = lending_market;
+    counter.owner = owner;
+    counter.bump = bumps.obligation_counter;
+    let index = counter.claim_index();
+
+    let obligation = &mut accounts.obligation;
+    obligation.init(lending_market, owner, index, bumps.obligation, clock.slot);
+    obligation.deposit(collateral_reserve_key, collateral_amount)?;
+    if borrow_amount > 0 {
+        obligation.borrow(
+            borrow_reserve_key,
+            borrow_amount,
+            accounts
+                .borrow_reserve
+                .liquidity
+                .cumulative_borrow_rate_wads,
+        )?;
+    }
+    obligation.refresh_values(&[
+        (
+            collateral_reserve_key,
+            (*accounts.collateral_reserve).clone(),
+        ),
+        (borrow_reserve_key, (*accounts.borrow_reserve).clone()),
+    ])?;
+
+    if check_borrow_limit {
+        require!(
+            obligation.is_within_borrow_limit(),
+            LiquidationError::BorrowTooLarge
+        );
+    }
+
+    accounts.collateral_reserve.collateral.deposited_amount = accounts
+        .collateral_reserve
+        .collateral
+        .deposited_amount
+        .checked_add(collateral_amount)
+        .ok_or(LiquidationError::MathOverflow)?;
+    transfer_to_vault(
+        &accounts.token_program,
+        &accounts.user_collateral,
+        &accounts.collateral_supply,
+        &accounts.user,
+        collateral_amount,
+    )?;
+
+    let obligation_key = accounts.obligation.key();
+    emit!(PositionOpened {
+        obligation: obligation_key,
+        lending_market,
+        owner,
+        index,
+    });
+    emit!(CollateralDeposited {
+        obligation: obligation_key,
+        reserve: collateral_reserve_key,
+        amount: collateral_amount,
+    });
+
+    if borrow_amount > 0 {
+        accounts.borrow_reserve.liquidity.borrow(borrow_amount)?;
+        accounts.borrow_reserve.last_update.mark_stale();
+        transfer_from_vault(
+            &accounts.token_program,
+            &accounts.liquidity_supply,
+            &accounts.user_debt,
+            &accounts.lending_market,
+            &accounts.lending_market_authority,
+            borrow_amount,
+        )?;
+        emit!(LiquidityBorrowed {
+            obligation: obligation_key,
+            reserve: borrow_reserve_key,
+            amount: borrow_amount,
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::OBLIGATION_SEED;
+use crate::error::LiquidationError;
+use crate::events::CollateralDeposited;
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::transfer_to_vault;
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        has_one = owner @ LiquidationError::Unauthorized,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub deposit_reserve: Account<'info, Reserve>,
+    #[account(mut, address = deposit_reserve.collateral.supply_pubkey)]
+    pub collateral_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = deposit_reserve.liquidity.mint_pubkey,
+        token::authority = owner,
+    )]
+    pub owner_collateral: Account<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn process_deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_not_paused()?;
+
+    let obligation = &mut ctx.accounts.obligation;
+    obligation.deposit(ctx.accounts.deposit_reserve.key(), amount)?;
+    obligation.last_update.mark_stale();
+
+    ctx.accounts.deposit_reserve.collateral.deposited_amount = ctx
+        .accounts
+        .deposit_reserve
+        .collateral
+        .deposited_amount
+        .checked_add(amount)
+        .ok_or(LiquidationError::MathOverflow)?;
+    transfer_to_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.owner_collateral,
+        &ctx.accounts.collateral_supply,
+        &ctx.accounts.owner,
+        amount,
+    )?;
+
+    emit!(CollateralDeposited {
+        obligation: ctx.accounts.obligation.key(),
+        reserve: ctx.accounts.deposit_reserve.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RISK_PROPOSAL_SEED;
+use crate::error::LiquidationError;
+use crate::events::RiskProposalExecuted;
+use crate::state::{LendingMarket, Reserve, RiskProposal};
+
+#[derive(Accounts)]
+pub struct ExecuteRiskProposal<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        mut,
+        has_one = lending_market,
+        has_one = reserve,
+        seeds = [
+            RISK_PROPOSAL_SEED,
+            proposal.lending_market.as_ref(),
+            &proposal.id.to_le_bytes(),
+        ],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, RiskProposal>,
+}
+
+/// Applies an approved proposal's risk parameters once its timelock has elapsed.
+/// Anyone may call it.
+pub fn process_execute_risk_proposal(ctx: Context<ExecuteRiskProposal>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        !proposal.executed,
+        LiquidationError::ProposalAlreadyExecuted
+    );
+    let approved_slot = proposal
+        .approved_slot
+        .ok_or(LiquidationError::ProposalNotApproved)?;
+    let slot = Clock::get()?.slot;
+    require!(
+        slot >= approved_slot.saturating_add(ctx.accounts.lending_market.governance.timelock_slots),
+        LiquidationError::TimelockNotElapsed
+    );
+
+    // Interest up to this slot compounds at the rates being replaced
+    let reserve = &mut ctx.accounts.reserve;
+    reserve.accrue_interest(slot)?;
+    let mut config = reserve.config;
+    config.set_risk_parameters(proposal.risk_parameters);
+    config.validate()?;
+    reserve.config = config;
+    reserve.last_update.mark_stale();
+    proposal.executed = true;
+
+    emit!(RiskProposalExecuted {
+        proposal: proposal.key(),
+        reserve: reserve.key(),
+        risk_parameters: proposal.risk_parameters,
+    });
+
+    Ok(())
+}
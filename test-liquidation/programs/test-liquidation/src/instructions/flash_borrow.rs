@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::LENDING_MARKET_AUTHORITY_SEED;
+use crate::error::LiquidationError;
+use crate::events::FlashBorrowed;
+use crate::instructions::FLASH_REPAY_RESERVE_INDEX;
+use crate::state::{LendingMarket, Reserve};
+use crate::utils::{current_top_level_index, transfer_from_vault};
+
+/// Position of `reserve` in `FlashBorrow`'s accounts, for `flash_repay` to check.
+pub const FLASH_BORROW_RESERVE_INDEX: usize = 2;
+
+#[derive(Accounts)]
+pub struct FlashBorrow<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = reserve.liquidity.mint_pubkey,
+    )]
+    pub destination_liquidity: Account<'info, TokenAccount>,
+    /// CHECK: Instructions sysvar, read to find the matching `flash_repay`.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Lends `amount` of reserve liquidity with no collateral, provided a `flash_repay` of
+/// the same amount from the same reserve follows later in the transaction. Other
+/// instructions may run in between, but not another flash borrow.
+pub fn process_flash_borrow(ctx: Context<FlashBorrow>, amount: u64) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_borrows_allowed()?;
+
+    let instructions = ctx.accounts.instructions.to_account_info();
+    let current_index = current_top_level_index(&instructions)?;
+    let reserve_key = ctx.accounts.reserve.key();
+    let mut index = current_index + 1;
+    loop {
+        let instruction = match load_instruction_at_checked(index, &instructions) {
+            Ok(instruction) => instruction,
+            Err(ProgramError::InvalidArgument) => return err!(LiquidationError::FlashRepayMissing),
+            Err(err) => return Err(err.into()),
+        };
+        index += 1;
+        if instruction.program_id != crate::ID {
+            continue;
+        }
+
+        let data = instruction.data.as_slice();
+        require!(
+            !data.starts_with(crate::instruction::FlashBorrow::DISCRIMINATOR),
+            LiquidationError::MultipleFlashBorrows
+        );
+        if let Some(args) = data.strip_prefix(crate::instruction::FlashRepay::DISCRIMINATOR) {
+            let repay = crate::instruction::FlashRepay::try_from_slice(args)?;
+            require!(
+                repay.amount == amount
+                    && repay.borrow_instruction_index as usize == current_index
+                    && instruction
+                        .accounts
+                        .get(FLASH_REPAY_RESERVE_INDEX)
+                        .is_some_and(|meta| meta.pubkey == reserve_key),
+                LiquidationError::InvalidFlashRepay
+            );
+            break;
+        }
+    }
+
+    // Interest up to this slot accrues at the utilization before the loan, so a
+    // refresh while it is outstanding has nothing left to charge at the inflated rate
+    let reserve = &mut ctx.accounts.reserve;
+    reserve.accrue_interest(Clock::get()?.slot)?;
+    require!(
+        amount <= reserve.liquidity.available_amount,
+        LiquidationError::InsufficientLiquidity
+    );
+    reserve.liquidity.available_amount = reserve
+        .liquidity
+        .available_amount
+        .checked_sub(amount)
+        .ok_or(LiquidationError::MathOverflow)?;
+    reserve.last_update.mark_stale();
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.destination_liquidity,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        amount,
+    )?;
+
+    emit!(FlashBorrowed {
+        reserve: reserve_key,
+        amount,
+    });
+
+    Ok(())
+}
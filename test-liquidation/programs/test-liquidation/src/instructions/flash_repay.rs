@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::error::LiquidationError;
+use crate::events::FlashRepaid;
+use crate::instructions::FLASH_BORROW_RESERVE_INDEX;
+use crate::state::{LendingMarket, Reserve};
+use crate::utils::{current_top_level_index, transfer_to_vault};
+
+/// Position of `reserve` in `FlashRepay`'s accounts, for `flash_borrow` to check.
+pub const FLASH_REPAY_RESERVE_INDEX: usize = 1;
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = reserve.liquidity.mint_pubkey,
+        token::authority = repayer,
+    )]
+    pub source_liquidity: Account<'info, TokenAccount>,
+    pub repayer: Signer<'info>,
+    /// CHECK: Instructions sysvar, read to find the matching `flash_borrow`.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Repays the flash loan taken by the `flash_borrow` at `borrow_instruction_index`
+/// earlier in the transaction, plus the reserve's flash loan fee, which stays with the
+/// reserve's liquidity.
+pub fn process_flash_repay(
+    ctx: Context<FlashRepay>,
+    amount: u64,
+    borrow_instruction_index: u8,
+) -> Result<()> {
+    let instructions = ctx.accounts.instructions.to_account_info();
+    let current_index = current_top_level_index(&instructions)?;
+    require!(
+        (borrow_instruction_index as usize) < current_index,
+        LiquidationError::InvalidFlashRepay
+    );
+    let borrow = load_instruction_at_checked(borrow_instruction_index as usize, &instructions)?;
+    let args = borrow
+        .data
+        .strip_prefix(crate::instruction::FlashBorrow::DISCRIMINATOR)
+        .filter(|_| borrow.program_id == crate::ID)
+        .ok_or(LiquidationError::InvalidFlashRepay)?;
+    let reserve_key = ctx.accounts.reserve.key();
+    require!(
+        crate::instruction::FlashBorrow::try_from_slice(args)?.amount == amount
+            && borrow
+                .accounts
+                .get(FLASH_BORROW_RESERVE_INDEX)
+                .is_some_and(|meta| meta.pubkey == reserve_key),
+        LiquidationError::InvalidFlashRepay
+    );
+
+    let reserve = &mut ctx.accounts.reserve;
+    let fee = reserve.config.flash_loan_fee_amount(amount)?;
+    let repay_amount = amount
+        .checked_add(fee)
+        .ok_or(LiquidationError::MathOverflow)?;
+    reserve.liquidity.available_amount = reserve
+        .liquidity
+        .available_amount
+        .checked_add(repay_amount)
+        .ok_or(LiquidationError::MathOverflow)?;
+    reserve.last_update.mark_stale();
+    transfer_to_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.source_liquidity,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.repayer,
+        repay_amount,
+    )?;
+
+    emit!(FlashRepaid {
+        reserve: reserve_key,
+        amount,
+        fee,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::error::LiquidationError;
+use crate::state::{LendingMarket, Reserve};
+use crate::utils::transfer_to_vault;
+
+#[derive(Accounts)]
+pub struct FundReserve<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = reserve.liquidity.mint_pubkey,
+        token::authority = funder,
+    )]
+    pub funder_liquidity: Account<'info, TokenAccount>,
+    pub funder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Seeds a reserve with liquidity so positions can borrow from it. Returns the supply
+/// shares issued for it.
+pub fn process_fund_reserve(ctx: Context<FundReserve>, amount: u64) -> Result<u64> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_not_paused()?;
+
+    // Settle interest at the old utilization before the new liquidity changes it
+    ctx.accounts.reserve.accrue_interest(Clock::get()?.slot)?;
+
+    transfer_to_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.funder_liquidity,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.funder,
+        amount,
+    )?;
+
+    ctx.accounts.reserve.liquidity.supply(amount)
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_GOVERNANCE_SIGNERS;
+use crate::error::LiquidationError;
+use crate::state::{Governance, LendingMarket};
+
+#[derive(Accounts)]
+pub struct InitGovernance<'info> {
+    #[account(mut, has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    pub owner: Signer<'info>,
+}
+
+/// Hands control of risk parameters to `signers`, `threshold` of whom must approve a
+/// change before it waits out `timelock_slots`. Can only be done once, so the owner
+/// cannot swap in a signer set that bypasses the existing one.
+pub fn process_init_governance(
+    ctx: Context<InitGovernance>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+    timelock_slots: u64,
+) -> Result<()> {
+    let lending_market = &mut ctx.accounts.lending_market;
+    require!(
+        !lending_market.governance.is_initialized(),
+        LiquidationError::GovernanceAlreadyInitialized
+    );
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_GOVERNANCE_SIGNERS,
+        LiquidationError::InvalidConfig
+    );
+    require!(
+        threshold > 0 && threshold as usize <= signers.len(),
+        LiquidationError::InvalidConfig
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require!(
+            !signers[..i].contains(signer),
+            LiquidationError::InvalidConfig
+        );
+    }
+
+    lending_market.governance = Governance {
+        signers,
+        threshold,
+        timelock_slots,
+        next_proposal_id: 0,
+    };
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::INSURANCE_FUND_SEED;
+use crate::error::LiquidationError;
+use crate::state::{InsuranceFund, LendingMarket};
+
+#[derive(Accounts)]
+pub struct InitInsuranceFund<'info> {
+    #[account(has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + InsuranceFund::INIT_SPACE,
+        seeds = [INSURANCE_FUND_SEED, lending_market.key().as_ref()],
+        bump,
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens the market's insurance fund to stakers, who must wait
+/// `unstake_cooldown_slots` between requesting an unstake and withdrawing.
+pub fn process_init_insurance_fund(
+    ctx: Context<InitInsuranceFund>,
+    unstake_cooldown_slots: u64,
+) -> Result<()> {
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.lending_market = ctx.accounts.lending_market.key();
+    insurance_fund.unstake_cooldown_slots = unstake_cooldown_slots;
+    insurance_fund.bump = ctx.bumps.insurance_fund;
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, PROGRAM_VERSION};
+use crate::state::LendingMarket;
+
+#[derive(Accounts)]
+pub struct InitLendingMarket<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + LendingMarket::INIT_SPACE
+    )]
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_init_lending_market(ctx: Context<InitLendingMarket>) -> Result<()> {
+    let lending_market_key = ctx.accounts.lending_market.key();
+    let (_, bump_seed) = Pubkey::find_program_address(
+        &[LENDING_MARKET_AUTHORITY_SEED, lending_market_key.as_ref()],
+        ctx.program_id,
+    );
+
+    let lending_market = &mut ctx.accounts.lending_market;
+    lending_market.version = PROGRAM_VERSION;
+    lending_market.bump_seed = bump_seed;
+    lending_market.owner = ctx.accounts.owner.key();
+
+    Ok(())
+}
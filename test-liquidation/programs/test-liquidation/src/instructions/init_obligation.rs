@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{OBLIGATION_COUNTER_SEED, OBLIGATION_SEED};
+use crate::events::PositionOpened;
+use crate::state::{LendingMarket, Obligation, ObligationCounter};
+
+#[derive(Accounts)]
+pub struct InitObligation<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ObligationCounter::INIT_SPACE,
+        seeds = [OBLIGATION_COUNTER_SEED, lending_market.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub obligation_counter: Account<'info, ObligationCounter>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Obligation::INIT_SPACE,
+        seeds = [
+            OBLIGATION_SEED,
+            lending_market.key().as_ref(),
+            owner.key().as_ref(),
+            &obligation_counter.next_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_init_obligation(ctx: Context<InitObligation>) -> Result<()> {
+    let lending_market = ctx.accounts.lending_market.key();
+    let owner = ctx.accounts.owner.key();
+
+    let counter = &mut ctx.accounts.obligation_counter;
+    counter.lending_market = lending_market;
+    counter.owner = owner;
+    counter.bump = ctx.bumps.obligation_counter;
+    let index = counter.claim_index();
+
+    ctx.accounts.obligation.init(
+        lending_market,
+        owner,
+        index,
+        ctx.bumps.obligation,
+        Clock::get()?.slot,
+    );
+
+    emit!(PositionOpened {
+        obligation: ctx.accounts.obligation.key(),
+        lending_market,
+        owner,
+        index,
+    });
+
+    Ok(())
+}
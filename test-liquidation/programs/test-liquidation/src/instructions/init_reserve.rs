@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::constants::{
+    COLLATERAL_SUPPLY_SEED, INSURANCE_VAULT_SEED, LENDING_MARKET_AUTHORITY_SEED,
+    LIQUIDITY_SUPPLY_SEED, MAX_RESERVE_ORACLES, PROGRAM_VERSION, RESERVE_SEED, TREASURY_SEED, WAD,
+};
+use crate::error::LiquidationError;
+use crate::oracle::get_market_price;
+use crate::state::{
+    LendingMarket, OracleSource, Reserve, ReserveCollateral, ReserveConfig, ReserveInsurance,
+    ReserveLiquidity, ReserveOracle,
+};
+
+/// Remaining accounts: the oracle accounts matching `oracle_sources`, primary first.
+#[derive(Accounts)]
+pub struct InitReserve<'info> {
+    #[account(has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA that owns the reserve vaults; only its address is used.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Reserve::INIT_SPACE,
+        seeds = [RESERVE_SEED, lending_market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    pub liquidity_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [LIQUIDITY_SUPPLY_SEED, reserve.key().as_ref()],
+        bump,
+        token::mint = liquidity_mint,
+        token::authority = lending_market_authority,
+    )]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [COLLATERAL_SUPPLY_SEED, reserve.key().as_ref()],
+        bump,
+        token::mint = liquidity_mint,
+        token::authority = lending_market_authority,
+    )]
+    pub collateral_supply: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TREASURY_SEED, reserve.key().as_ref()],
+        bump,
+        token::mint = liquidity_mint,
+        token::authority = lending_market_authority,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump,
+        token::mint = liquidity_mint,
+        token::authority = lending_market_authority,
+    )]
+    pub insurance_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_init_reserve(
+    ctx: Context<InitReserve>,
+    config: ReserveConfig,
+    oracle_sources: Vec<OracleSource>,
+) -> Result<()> {
+    config.validate()?;
+    require!(
+        !oracle_sources.is_empty() && oracle_sources.len() <= MAX_RESERVE_ORACLES,
+        LiquidationError::InvalidConfig
+    );
+    require!(
+        ctx.remaining_accounts.len() == oracle_sources.len(),
+        LiquidationError::InvalidOracle
+    );
+
+    let mut oracles = [ReserveOracle::default(); MAX_RESERVE_ORACLES];
+    for ((oracle, source), info) in oracles
+        .iter_mut()
+        .zip(oracle_sources)
+        .zip(ctx.remaining_accounts)
+    {
+        *oracle = ReserveOracle {
+            pubkey: info.key(),
+            source,
+        };
+    }
+
+    let reserve = &mut ctx.accounts.reserve;
+    let slot = Clock::get()?.slot;
+    reserve.version = PROGRAM_VERSION;
+    reserve.last_update.update_slot(slot);
+    reserve.lending_market = ctx.accounts.lending_market.key();
+    reserve.liquidity = ReserveLiquidity {
+        mint_pubkey: ctx.accounts.liquidity_mint.key(),
+        mint_decimals: ctx.accounts.liquidity_mint.decimals,
+        supply_pubkey: ctx.accounts.liquidity_supply.key(),
+        oracles,
+        cumulative_borrow_rate_wads: WAD,
+        ..ReserveLiquidity::default()
+    };
+    reserve.collateral = ReserveCollateral {
+        supply_pubkey: ctx.accounts.collateral_supply.key(),
+        deposited_amount: 0,
+        treasury_pubkey: ctx.accounts.treasury.key(),
+    };
+    reserve.insurance = ReserveInsurance {
+        vault_pubkey: ctx.accounts.insurance_vault.key(),
+        ..ReserveInsurance::default()
+    };
+    reserve.config = config;
+    reserve.liquidity.market_price =
+        get_market_price(reserve.key(), reserve, ctx.remaining_accounts)?;
+    reserve.liquidity.market_price_slot = slot;
+    reserve.bump = ctx.bumps.reserve;
+
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::ObligationLiquidated;
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::{transfer_from_vault, transfer_to_vault};
+
+use super::write_off_bad_debt::write_off_bad_debt;
+
+#[derive(Accounts)]
+pub struct Liquidate<'info> {
+    #[account(
+        mut,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub repay_reserve: Account<'info, Reserve>,
+    #[account(mut, address = repay_reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(mut, address = repay_reserve.insurance.vault_pubkey)]
+    pub insurance_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = lending_market,
+        constraint = withdraw_reserve.key() != repay_reserve.key() @ LiquidationError::DuplicateReserve,
+    )]
+    pub withdraw_reserve: Account<'info, Reserve>,
+    #[account(mut, address = withdraw_reserve.collateral.supply_pubkey)]
+    pub collateral_supply: Account<'info, TokenAccount>,
+    #[account(mut, address = withdraw_reserve.collateral.treasury_pubkey)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(mut, address = withdraw_reserve.insurance.vault_pubkey)]
+    pub collateral_insurance_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.liquidity.mint_pubkey,
+    )]
+    pub liquidator_collateral: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity.mint_pubkey,
+        token::authority = liquidator,
+    )]
+    pub liquidator_debt: Account<'info, TokenAccount>,
+    pub liquidator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Repays up to `repay_amount` of the obligation's borrow from `repay_reserve`, at
+/// most the reserve's close factor of it, and seizes collateral worth the repaid value
+/// plus `withdraw_reserve`'s liquidation bonus, once oracle prices put the borrowed
+/// value above the liquidation threshold. The obligation and both reserves must have
+/// been refreshed earlier in the same slot. The protocol's share of the bonus is split
+/// between the reserve treasury and its insurance vault, and the rest goes to the
+/// liquidator. When no collateral is left, the
+/// rest of the borrow from `repay_reserve` is written off as bad debt. Returns the
+/// seized collateral amount.
+pub fn process_liquidate(ctx: Context<Liquidate>, repay_amount: u64) -> Result<u64> {
+    require!(repay_amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_liquidations_allowed()?;
+
+    let slot = Clock::get()?.slot;
+    require!(
+        !ctx.accounts.repay_reserve.last_update.is_stale(slot)
+            && !ctx.accounts.withdraw_reserve.last_update.is_stale(slot),
+        LiquidationError::ReserveStale
+    );
+    let obligation = &mut ctx.accounts.obligation;
+    require!(
+        !obligation.last_update.is_stale(slot),
+        LiquidationError::ObligationStale
+    );
+
+    let health_factor_before = obligation.health_factor()?;
+    msg!("Obligation health factor (WAD): {}", health_factor_before);
+    require!(
+        obligation.is_liquidatable(),
+        LiquidationError::NotLiquidatable
+    );
+
+    let repay_reserve_key = ctx.accounts.repay_reserve.key();
+    let withdraw_reserve_key = ctx.accounts.withdraw_reserve.key();
+    require!(
+        repay_amount
+            <= obligation.max_liquidation_amount(
+                repay_reserve_key,
+                ctx.accounts.repay_reserve.config.liquidation_close_factor,
+            )?,
+        LiquidationError::LiquidationTooLarge
+    );
+    let (repaid_amount, seized_amount) = obligation.calculate_liquidation(
+        repay_reserve_key,
+        withdraw_reserve_key,
+        repay_amount,
+        ctx.accounts.withdraw_reserve.config.liquidation_bonus,
+    )?;
+    obligation.liquidate(
+        repay_reserve_key,
+        ctx.accounts
+            .repay_reserve
+            .liquidity
+            .cumulative_borrow_rate_wads,
+        repaid_amount,
+        withdraw_reserve_key,
+        &ctx.accounts.withdraw_reserve.config,
+        seized_amount,
+    )?;
+    obligation.last_update.mark_stale();
+    let health_factor_after = obligation.health_factor()?;
+
+    // Liquidator repays the debt, then receives the collateral
+    ctx.accounts.repay_reserve.liquidity.repay(repaid_amount)?;
+    ctx.accounts.repay_reserve.last_update.mark_stale();
+    transfer_to_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.liquidator_debt,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.liquidator,
+        repaid_amount,
+    )?;
+    ctx.accounts.withdraw_reserve.collateral.deposited_amount = ctx
+        .accounts
+        .withdraw_reserve
+        .collateral
+        .deposited_amount
+        .checked_sub(seized_amount)
+        .ok_or(LiquidationError::MathOverflow)?;
+    let withdraw_config = &ctx.accounts.withdraw_reserve.config;
+    let bonus_amount = withdraw_config.liquidation_bonus_amount(seized_amount)?;
+    let protocol_fee = withdraw_config.protocol_liquidation_fee_amount(seized_amount)?;
+    let insurance_fee = withdraw_config.insurance_liquidation_fee_amount(protocol_fee)?;
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.collateral_supply,
+        &ctx.accounts.liquidator_collateral,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        seized_amount - protocol_fee,
+    )?;
+    if protocol_fee > insurance_fee {
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.collateral_supply,
+            &ctx.accounts.treasury,
+            &ctx.accounts.lending_market,
+            &ctx.accounts.lending_market_authority,
+            protocol_fee - insurance_fee,
+        )?;
+    }
+    if insurance_fee > 0 {
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.collateral_supply,
+            &ctx.accounts.collateral_insurance_vault,
+            &ctx.accounts.lending_market,
+            &ctx.accounts.lending_market_authority,
+            insurance_fee,
+        )?;
+    }
+
+    emit!(ObligationLiquidated {
+        obligation: ctx.accounts.obligation.key(),
+        liquidator: ctx.accounts.liquidator.key(),
+        repay_reserve: repay_reserve_key,
+        withdraw_reserve: withdraw_reserve_key,
+        repaid_amount,
+        seized_amount,
+        bonus_amount,
+        protocol_fee,
+        insurance_fee,
+        health_factor_before,
+        health_factor_after,
+    });
+
+    if ctx.accounts.obligation.deposits.is_empty()
+        && ctx
+            .accounts
+            .obligation
+            .find_borrow_index(repay_reserve_key)
+            .is_ok()
+    {
+        write_off_bad_debt(
+            &mut ctx.accounts.obligation,
+            &mut ctx.accounts.repay_reserve,
+            &ctx.accounts.liquidity_supply,
+            &ctx.accounts.insurance_vault,
+            &ctx.accounts.lending_market,
+            &ctx.accounts.lending_market_authority,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    Ok(seized_amount)
+}
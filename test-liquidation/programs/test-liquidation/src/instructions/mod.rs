@@ -0,0 +1,61 @@
+pub mod approve_risk_proposal;
+pub mod borrow;
+pub mod close_empty_position;
+pub mod close_position;
+pub mod collect_insurance_fees;
+pub mod create_position;
+pub mod deposit;
+pub mod execute_risk_proposal;
+pub mod flash_borrow;
+pub mod flash_repay;
+pub mod fund_reserve;
+pub mod init_governance;
+pub mod init_insurance_fund;
+pub mod init_lending_market;
+pub mod init_obligation;
+pub mod init_reserve;
+pub mod liquidate;
+pub mod propose_risk_parameters;
+pub mod refresh_obligation;
+pub mod refresh_reserve;
+pub mod repay;
+pub mod request_insurance_unstake;
+pub mod set_circuit_breaker;
+pub mod set_market_pauses;
+pub mod stake_insurance;
+pub mod unstake_insurance;
+pub mod update_reserve_config;
+pub mod withdraw;
+pub mod withdraw_protocol_fees;
+pub mod write_off_bad_debt;
+
+pub use approve_risk_proposal::*;
+pub use borrow::*;
+pub use close_empty_position::*;
+pub use close_position::*;
+pub use collect_insurance_fees::*;
+pub use create_position::*;
+pub use deposit::*;
+pub use execute_risk_proposal::*;
+pub use flash_borrow::*;
+pub use flash_repay::*;
+pub use fund_reserve::*;
+pub use init_governance::*;
+pub use init_insurance_fund::*;
+pub use init_lending_market::*;
+pub use init_obligation::*;
+pub use init_reserve::*;
+pub use liquidate::*;
+pub use propose_risk_parameters::*;
+pub use refresh_obligation::*;
+pub use refresh_reserve::*;
+pub use repay::*;
+pub use request_insurance_unstake::*;
+pub use set_circuit_breaker::*;
+pub use set_market_pauses::*;
+pub use stake_insurance::*;
+pub use unstake_insurance::*;
+pub use update_reserve_config::*;
+pub use withdraw::*;
+pub use withdraw_protocol_fees::*;
+pub use write_off_bad_debt::*;
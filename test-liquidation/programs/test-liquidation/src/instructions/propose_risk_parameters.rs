@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RISK_PROPOSAL_SEED;
+use crate::error::LiquidationError;
+use crate::events::RiskProposalCreated;
+use crate::state::{LendingMarket, Reserve, RiskParameters, RiskProposal};
+
+#[derive(Accounts)]
+pub struct ProposeRiskParameters<'info> {
+    #[account(mut)]
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + RiskProposal::INIT_SPACE,
+        seeds = [
+            RISK_PROPOSAL_SEED,
+            lending_market.key().as_ref(),
+            &lending_market.governance.next_proposal_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub proposal: Account<'info, RiskProposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Proposes new risk parameters for a reserve, counting as the proposer's approval.
+pub fn process_propose_risk_parameters(
+    ctx: Context<ProposeRiskParameters>,
+    risk_parameters: RiskParameters,
+) -> Result<()> {
+    let governance = &ctx.accounts.lending_market.governance;
+    require!(
+        governance.is_signer(&ctx.accounts.proposer.key()),
+        LiquidationError::Unauthorized
+    );
+    let mut config = ctx.accounts.reserve.config;
+    config.set_risk_parameters(risk_parameters);
+    config.validate()?;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.lending_market = ctx.accounts.lending_market.key();
+    proposal.reserve = ctx.accounts.reserve.key();
+    proposal.id = governance.next_proposal_id;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.risk_parameters = risk_parameters;
+    proposal.bump = ctx.bumps.proposal;
+    proposal.approve(
+        ctx.accounts.proposer.key(),
+        governance.threshold,
+        Clock::get()?.slot,
+    );
+    ctx.accounts.lending_market.governance.next_proposal_id += 1;
+
+    emit!(RiskProposalCreated {
+        proposal: ctx.accounts.proposal.key(),
+        reserve: ctx.accounts.reserve.key(),
+        proposer: ctx.accounts.proposer.key(),
+        risk_parameters,
+    });
+
+    Ok(())
+}
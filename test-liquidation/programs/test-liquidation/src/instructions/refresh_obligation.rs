@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LiquidationError;
+use crate::state::{Obligation, Reserve};
+
+/// Remaining accounts: every reserve the obligation references, each refreshed
+/// earlier in the same slot.
+#[derive(Accounts)]
+pub struct RefreshObligation<'info> {
+    #[account(mut)]
+    pub obligation: Account<'info, Obligation>,
+}
+
+/// Accrues interest on the obligation's borrows and recomputes its cached values
+/// from freshly refreshed reserves, leaving it fresh for the rest of the slot.
+pub fn process_refresh_obligation(ctx: Context<RefreshObligation>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    let reserves = ctx
+        .remaining_accounts
+        .iter()
+        .map(|info| {
+            require_keys_eq!(
+                *info.owner,
+                crate::ID,
+                ErrorCode::AccountOwnedByWrongProgram
+            );
+            let reserve = Reserve::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(
+                reserve.lending_market,
+                ctx.accounts.obligation.lending_market,
+                ErrorCode::ConstraintHasOne
+            );
+            require!(
+                !reserve.last_update.is_stale(slot),
+                LiquidationError::ReserveStale
+            );
+            Ok((info.key(), reserve))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let obligation = &mut ctx.accounts.obligation;
+    obligation.refresh_values(&reserves)?;
+    obligation.last_update.update_slot(slot);
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::events::CircuitBreakerTripped;
+use crate::oracle::get_market_price;
+use crate::state::{LendingMarket, Reserve};
+
+/// Remaining accounts: the reserve's oracles in configured order.
+#[derive(Accounts)]
+pub struct RefreshReserve<'info> {
+    #[account(mut)]
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+}
+
+/// Accrues interest on a reserve and reprices it from its oracles, leaving it fresh
+/// for the rest of the slot. A price moving past the market's circuit breaker halts
+/// borrowing rather than failing the refresh, so the trip is recorded.
+pub fn process_refresh_reserve(ctx: Context<RefreshReserve>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    let reserve_key = ctx.accounts.reserve.key();
+    let reserve = &mut ctx.accounts.reserve;
+    reserve.accrue_interest(slot)?;
+    let market_price = get_market_price(reserve_key, reserve, ctx.remaining_accounts)?;
+    reserve.liquidity.market_price = market_price;
+    reserve.liquidity.market_price_slot = slot;
+    reserve.last_update.update_slot(slot);
+
+    track_price_move(
+        &mut ctx.accounts.lending_market,
+        &mut ctx.accounts.reserve,
+        market_price,
+        slot,
+    )
+}
+
+/// Feeds a freshly read price to the market's circuit breaker, tripping it when the
+/// price moved too far within the window.
+pub(crate) fn track_price_move(
+    lending_market: &mut Account<LendingMarket>,
+    reserve: &mut Account<Reserve>,
+    market_price: u128,
+    slot: u64,
+) -> Result<()> {
+    let reference_price = reserve.liquidity.breaker_reference_price;
+    if reserve
+        .liquidity
+        .track_price_move(market_price, slot, &lending_market.circuit_breaker)?
+        && !lending_market.circuit_breaker.tripped
+    {
+        lending_market.circuit_breaker.tripped = true;
+        emit!(CircuitBreakerTripped {
+            lending_market: lending_market.key(),
+            reserve: reserve.key(),
+            reference_price,
+            market_price,
+        });
+    }
+
+    Ok(())
+}
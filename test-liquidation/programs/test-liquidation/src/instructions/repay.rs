@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::OBLIGATION_SEED;
+use crate::error::LiquidationError;
+use crate::events::LiquidityRepaid;
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::transfer_to_vault;
+
+#[derive(Accounts)]
+pub struct Repay<'info> {
+    #[account(
+        mut,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub repay_reserve: Account<'info, Reserve>,
+    #[account(mut, address = repay_reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = repay_reserve.liquidity.mint_pubkey,
+        token::authority = repayer,
+    )]
+    pub repayer_debt: Account<'info, TokenAccount>,
+    pub repayer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Repays up to `amount` of debt; anything above the outstanding balance is ignored.
+pub fn process_repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_not_paused()?;
+
+    let repay_reserve = &mut ctx.accounts.repay_reserve;
+    repay_reserve.accrue_interest(Clock::get()?.slot)?;
+    let obligation = &mut ctx.accounts.obligation;
+    let repay_amount = obligation.repay(
+        repay_reserve.key(),
+        amount,
+        repay_reserve.liquidity.cumulative_borrow_rate_wads,
+    )?;
+    obligation.last_update.mark_stale();
+
+    repay_reserve.liquidity.repay(repay_amount)?;
+    repay_reserve.last_update.mark_stale();
+    transfer_to_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.repayer_debt,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.repayer,
+        repay_amount,
+    )?;
+
+    emit!(LiquidityRepaid {
+        obligation: ctx.accounts.obligation.key(),
+        reserve: ctx.accounts.repay_reserve.key(),
+        repayer: ctx.accounts.repayer.key(),
+        amount: repay_amount,
+    });
+
+    Ok(())
+}
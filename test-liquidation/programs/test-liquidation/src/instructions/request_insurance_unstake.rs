@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::INSURANCE_STAKE_SEED;
+use crate::error::LiquidationError;
+use crate::state::InsuranceStake;
+
+#[derive(Accounts)]
+pub struct RequestInsuranceUnstake<'info> {
+    #[account(
+        mut,
+        has_one = staker @ LiquidationError::Unauthorized,
+        seeds = [
+            INSURANCE_STAKE_SEED,
+            insurance_stake.reserve.as_ref(),
+            insurance_stake.staker.as_ref(),
+        ],
+        bump = insurance_stake.bump,
+    )]
+    pub insurance_stake: Account<'info, InsuranceStake>,
+    pub staker: Signer<'info>,
+}
+
+/// Starts the cooldown on `shares` of a stake. Shares already waiting start over with
+/// them.
+pub fn process_request_insurance_unstake(
+    ctx: Context<RequestInsuranceUnstake>,
+    shares: u64,
+) -> Result<()> {
+    require!(shares > 0, LiquidationError::InvalidAmount);
+
+    let stake = &mut ctx.accounts.insurance_stake;
+    stake.shares = stake
+        .shares
+        .checked_sub(shares)
+        .ok_or(LiquidationError::InsufficientInsuranceShares)?;
+    stake.unstaking_shares = stake
+        .unstaking_shares
+        .checked_add(shares)
+        .ok_or(LiquidationError::MathOverflow)?;
+    stake.unstake_requested_slot = Clock::get()?.slot;
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LiquidationError;
+use crate::state::{CircuitBreaker, LendingMarket};
+
+#[derive(Accounts)]
+pub struct SetCircuitBreaker<'info> {
+    #[account(mut, has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    pub owner: Signer<'info>,
+}
+
+/// Halts borrowing whenever a reserve's price moves more than `max_price_move_bps`
+/// within `window_slots`, and resets a tripped breaker. Zero bps disables it.
+pub fn process_set_circuit_breaker(
+    ctx: Context<SetCircuitBreaker>,
+    max_price_move_bps: u16,
+    window_slots: u64,
+) -> Result<()> {
+    require!(
+        max_price_move_bps <= 10_000,
+        LiquidationError::InvalidConfig
+    );
+
+    ctx.accounts.lending_market.circuit_breaker = CircuitBreaker {
+        max_price_move_bps,
+        window_slots,
+        tripped: false,
+    };
+
+    Ok(())
+}
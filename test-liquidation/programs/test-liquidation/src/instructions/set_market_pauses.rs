@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LiquidationError;
+use crate::events::MarketPausesUpdated;
+use crate::state::{LendingMarket, MarketPauses};
+
+#[derive(Accounts)]
+pub struct SetMarketPauses<'info> {
+    #[account(mut, has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    pub owner: Signer<'info>,
+}
+
+/// Replaces the set of paused operations; pass all flags cleared to unpause.
+pub fn process_set_market_pauses(
+    ctx: Context<SetMarketPauses>,
+    pauses: MarketPauses,
+) -> Result<()> {
+    ctx.accounts.lending_market.pauses = pauses;
+
+    emit!(MarketPausesUpdated {
+        lending_market: ctx.accounts.lending_market.key(),
+        pauses,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{INSURANCE_FUND_SEED, INSURANCE_STAKE_SEED};
+use crate::error::LiquidationError;
+use crate::events::InsuranceStaked;
+use crate::state::{InsuranceFund, InsuranceStake, LendingMarket, Reserve};
+use crate::utils::transfer_to_vault;
+
+#[derive(Accounts)]
+pub struct StakeInsurance<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(
+        has_one = lending_market,
+        seeds = [INSURANCE_FUND_SEED, lending_market.key().as_ref()],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.insurance.vault_pubkey)]
+    pub insurance_vault: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = staker,
+        space = 8 + InsuranceStake::INIT_SPACE,
+        seeds = [INSURANCE_STAKE_SEED, reserve.key().as_ref(), staker.key().as_ref()],
+        bump,
+    )]
+    pub insurance_stake: Account<'info, InsuranceStake>,
+    #[account(
+        mut,
+        token::mint = reserve.liquidity.mint_pubkey,
+        token::authority = staker,
+    )]
+    pub staker_liquidity: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub staker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Stakes `amount` into the reserve's insurance vault for shares of it, priced at the
+/// vault's balance so fees paid in and bad debt paid out are shared pro rata.
+pub fn process_stake_insurance(ctx: Context<StakeInsurance>, amount: u64) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_not_paused()?;
+
+    let shares = ctx
+        .accounts
+        .reserve
+        .insurance
+        .stake(amount, ctx.accounts.insurance_vault.amount)?;
+    let stake = &mut ctx.accounts.insurance_stake;
+    stake.reserve = ctx.accounts.reserve.key();
+    stake.staker = ctx.accounts.staker.key();
+    stake.bump = ctx.bumps.insurance_stake;
+    stake.sync_share_epoch(ctx.accounts.reserve.insurance.share_epoch);
+    stake.shares = stake
+        .shares
+        .checked_add(shares)
+        .ok_or(LiquidationError::MathOverflow)?;
+
+    transfer_to_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.staker_liquidity,
+        &ctx.accounts.insurance_vault,
+        &ctx.accounts.staker,
+        amount,
+    )?;
+
+    emit!(InsuranceStaked {
+        reserve: ctx.accounts.reserve.key(),
+        staker: ctx.accounts.staker.key(),
+        amount,
+        shares,
+    });
+
+    Ok(())
+}
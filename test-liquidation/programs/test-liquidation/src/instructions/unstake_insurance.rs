@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{INSURANCE_FUND_SEED, INSURANCE_STAKE_SEED, LENDING_MARKET_AUTHORITY_SEED};
+use crate::error::LiquidationError;
+use crate::events::InsuranceUnstaked;
+use crate::state::{InsuranceFund, InsuranceStake, LendingMarket, Reserve};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct UnstakeInsurance<'info> {
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(
+        has_one = lending_market,
+        seeds = [INSURANCE_FUND_SEED, lending_market.key().as_ref()],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.insurance.vault_pubkey)]
+    pub insurance_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = staker @ LiquidationError::Unauthorized,
+        has_one = reserve,
+        seeds = [INSURANCE_STAKE_SEED, reserve.key().as_ref(), staker.key().as_ref()],
+        bump = insurance_stake.bump,
+    )]
+    pub insurance_stake: Account<'info, InsuranceStake>,
+    #[account(
+        mut,
+        token::mint = reserve.liquidity.mint_pubkey,
+    )]
+    pub destination_liquidity: Account<'info, TokenAccount>,
+    pub staker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Withdraws every share whose unstake cooldown has elapsed, at the vault's balance
+/// now, so bad debt covered during the cooldown is still borne by them. Returns the
+/// amount withdrawn.
+pub fn process_unstake_insurance(ctx: Context<UnstakeInsurance>) -> Result<u64> {
+    ctx.accounts.lending_market.check_not_paused()?;
+    let stake = &mut ctx.accounts.insurance_stake;
+    stake.sync_share_epoch(ctx.accounts.reserve.insurance.share_epoch);
+    let shares = stake.unstaking_shares;
+    require!(shares > 0, LiquidationError::InsufficientInsuranceShares);
+    require!(
+        Clock::get()?.slot
+            >= stake
+                .unstake_requested_slot
+                .saturating_add(ctx.accounts.insurance_fund.unstake_cooldown_slots),
+        LiquidationError::InsuranceCooldownActive
+    );
+
+    let amount = ctx
+        .accounts
+        .reserve
+        .insurance
+        .unstake(shares, ctx.accounts.insurance_vault.amount)?;
+    ctx.accounts.insurance_stake.unstaking_shares = 0;
+    if amount > 0 {
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.insurance_vault,
+            &ctx.accounts.destination_liquidity,
+            &ctx.accounts.lending_market,
+            &ctx.accounts.lending_market_authority,
+            amount,
+        )?;
+    }
+
+    emit!(InsuranceUnstaked {
+        reserve: ctx.accounts.reserve.key(),
+        staker: ctx.accounts.staker.key(),
+        shares,
+        amount,
+    });
+
+    Ok(amount)
+}
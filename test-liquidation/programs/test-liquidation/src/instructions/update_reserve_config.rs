@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LiquidationError;
+use crate::state::{LendingMarket, Reserve, ReserveConfig};
+
+#[derive(Accounts)]
+pub struct UpdateReserveConfig<'info> {
+    #[account(has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    pub owner: Signer<'info>,
+}
+
+/// Changes a reserve's oracle, fee and insurance settings. Its risk parameters must be
+/// left as they are; those change through governance proposals.
+pub fn process_update_reserve_config(
+    ctx: Context<UpdateReserveConfig>,
+    config: ReserveConfig,
+) -> Result<()> {
+    config.validate()?;
+    require!(
+        config.risk_parameters() == ctx.accounts.reserve.config.risk_parameters(),
+        LiquidationError::RiskParametersGoverned
+    );
+
+    // Interest accrued so far owes the insurance fund its old share
+    let reserve = &mut ctx.accounts.reserve;
+    reserve.accrue_interest(Clock::get()?.slot)?;
+    reserve.config = config;
+
+    Ok(())
+}
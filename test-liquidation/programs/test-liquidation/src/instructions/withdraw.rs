@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::CollateralWithdrawn;
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        has_one = owner @ LiquidationError::Unauthorized,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub withdraw_reserve: Account<'info, Reserve>,
+    #[account(mut, address = withdraw_reserve.collateral.supply_pubkey)]
+    pub collateral_supply: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = withdraw_reserve.liquidity.mint_pubkey,
+    )]
+    pub destination_collateral: Account<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Withdraws collateral from a refreshed obligation, as long as the remaining
+/// deposits still support its borrows.
+pub fn process_withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_withdrawals_allowed()?;
+
+    let slot = Clock::get()?.slot;
+    let withdraw_reserve = &mut ctx.accounts.withdraw_reserve;
+    let obligation = &mut ctx.accounts.obligation;
+    require!(
+        !withdraw_reserve.last_update.is_stale(slot),
+        LiquidationError::ReserveStale
+    );
+    require!(
+        !obligation.last_update.is_stale(slot),
+        LiquidationError::ObligationStale
+    );
+    require!(
+        amount <= obligation.max_withdraw_amount(withdraw_reserve.key(), withdraw_reserve)?,
+        LiquidationError::WithdrawTooLarge
+    );
+
+    obligation.withdraw(withdraw_reserve.key(), amount)?;
+    obligation.last_update.mark_stale();
+
+    withdraw_reserve.collateral.deposited_amount = withdraw_reserve
+        .collateral
+        .deposited_amount
+        .checked_sub(amount)
+        .ok_or(LiquidationError::MathOverflow)?;
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.collateral_supply,
+        &ctx.accounts.destination_collateral,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        amount,
+    )?;
+
+    emit!(CollateralWithdrawn {
+        obligation: ctx.accounts.obligation.key(),
+        reserve: ctx.accounts.withdraw_reserve.key(),
+        amount,
+    });
+
+    Ok(())
+}
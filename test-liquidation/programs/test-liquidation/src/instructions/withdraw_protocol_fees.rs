@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::LENDING_MARKET_AUTHORITY_SEED;
+use crate::error::LiquidationError;
+use crate::state::{LendingMarket, Reserve};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(has_one = owner @ LiquidationError::Unauthorized)]
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.collateral.treasury_pubkey)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = reserve.liquidity.mint_pubkey,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Moves accumulated protocol liquidation fees out of a reserve's treasury.
+pub fn process_withdraw_protocol_fees(
+    ctx: Context<WithdrawProtocolFees>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, LiquidationError::InvalidAmount);
+    ctx.accounts.lending_market.check_not_paused()?;
+    require!(
+        amount <= ctx.accounts.treasury.amount,
+        LiquidationError::InsufficientLiquidity
+    );
+
+    transfer_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.treasury,
+        &ctx.accounts.destination,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        amount,
+    )
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{LENDING_MARKET_AUTHORITY_SEED, OBLIGATION_SEED};
+use crate::error::LiquidationError;
+use crate::events::BadDebtWrittenOff;
+use crate::state::{LendingMarket, Obligation, Reserve};
+use crate::utils::transfer_from_vault;
+
+#[derive(Accounts)]
+pub struct WriteOffBadDebt<'info> {
+    #[account(
+        mut,
+        has_one = lending_market,
+        seeds = [
+            OBLIGATION_SEED,
+            obligation.lending_market.as_ref(),
+            obligation.owner.as_ref(),
+            &obligation.index.to_le_bytes(),
+        ],
+        bump = obligation.bump,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub lending_market: Account<'info, LendingMarket>,
+    /// CHECK: PDA signing for the reserve vaults.
+    #[account(
+        seeds = [LENDING_MARKET_AUTHORITY_SEED, lending_market.key().as_ref()],
+        bump = lending_market.bump_seed,
+    )]
+    pub lending_market_authority: UncheckedAccount<'info>,
+    #[account(mut, has_one = lending_market)]
+    pub reserve: Account<'info, Reserve>,
+    #[account(mut, address = reserve.liquidity.supply_pubkey)]
+    pub liquidity_supply: Account<'info, TokenAccount>,
+    #[account(mut, address = reserve.insurance.vault_pubkey)]
+    pub insurance_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Writes off an obligation's borrow from `reserve` once liquidations have seized all
+/// of its collateral. `liquidate` does this for the reserve it repays; this crank
+/// clears borrows left in other reserves. Anyone may call it.
+pub fn process_write_off_bad_debt(ctx: Context<WriteOffBadDebt>) -> Result<()> {
+    ctx.accounts.lending_market.check_not_paused()?;
+    ctx.accounts.reserve.accrue_interest(Clock::get()?.slot)?;
+    write_off_bad_debt(
+        &mut ctx.accounts.obligation,
+        &mut ctx.accounts.reserve,
+        &ctx.accounts.liquidity_supply,
+        &ctx.accounts.insurance_vault,
+        &ctx.accounts.lending_market,
+        &ctx.accounts.lending_market_authority,
+        &ctx.accounts.token_program,
+    )
+}
+
+/// Drops the obligation's borrow from `reserve`, covering what the insurance vault can
+/// by moving its funds into the liquidity supply and socializing the rest.
+pub(crate) fn write_off_bad_debt<'info>(
+    obligation: &mut Account<'info, Obligation>,
+    reserve: &mut Account<'info, Reserve>,
+    liquidity_supply: &Account<'info, TokenAccount>,
+    insurance_vault: &Account<'info, TokenAccount>,
+    lending_market: &Account<'info, LendingMarket>,
+    lending_market_authority: &UncheckedAccount<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let bad_debt = obligation
+        .write_off_borrow(reserve.key(), reserve.liquidity.cumulative_borrow_rate_wads)?;
+    obligation.last_update.mark_stale();
+    let insurance_covered = reserve
+        .liquidity
+        .write_off_bad_debt(bad_debt, insurance_vault.amount)?;
+    reserve.insurance.covered_amount = reserve
+        .insurance
+        .covered_amount
+        .checked_add(insurance_covered)
+        .ok_or(LiquidationError::MathOverflow)?;
+    reserve.last_update.mark_stale();
+    if insurance_covered > 0 {
+        transfer_from_vault(
+            token_program,
+            insurance_vault,
+            liquidity_supply,
+            lending_market,
+            lending_market_authority,
+            insurance_covered,
+        )?;
+    }
+
+    let bad_debt = bad_debt.try_ceil_u64()?;
+    emit!(BadDebtWrittenOff {
+        obligation: obligation.key(),
+        reserve: reserve.key(),
+        bad_debt,
+        insurance_covered,
+        socialized: bad_debt - insurance_covered,
+        supply_exchange_rate: reserve.liquidity.supply_exchange_rate()?.to_scaled_val()?,
+    });
+
+    Ok(())
+}
@@ -1,63 +1,184 @@
+// The IDL instructions generated by `#[program]` still call `AccountInfo::realloc`.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 
+pub mod constants;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod math;
+pub mod oracle;
+pub mod state;
+pub mod utils;
+
+pub use error::*;
+pub use events::*;
+pub use instructions::*;
+pub use state::*;
+
 declare_id!("6qBNpKqHkGG5xMdJd2zivWMKn2Ym3sqjj4xbD2N13eyH"); // We'll update this after building
 
 #[program]
 pub mod test_liquidation {
     use super::*;
 
-    pub fn create_risky_position(ctx: Context<CreatePosition>) -> Result<()> {
-        let position = &mut ctx.accounts.position;
-        
-        // Deposit 0.1 SOL but borrow 0.2 SOL worth of value
-        position.collateral_amount = 100_000_000; // 0.1 SOL
-        position.borrowed_amount = 200_000_000;   // 0.2 SOL equivalent
-        position.owner = ctx.accounts.user.key();
-        
-        Ok(())
-    }
-
-    pub fn liquidate(ctx: Context<Liquidate>) -> Result<()> {
-        let position = &mut ctx.accounts.position;
-        require!(position.borrowed_amount > position.collateral_amount, LiquidationError::NotLiquidatable);
-        
-        // Transfer collateral to liquidator
-        position.collateral_amount = 0;
-        position.borrowed_amount = 0;
-        
-        Ok(())
+    pub fn init_lending_market(ctx: Context<InitLendingMarket>) -> Result<()> {
+        instructions::process_init_lending_market(ctx)
     }
-}
 
-#[derive(Accounts)]
-pub struct CreatePosition<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + 32 + 8 + 8
-    )]
-    pub position: Account<'info, Position>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn init_reserve(
+        ctx: Context<InitReserve>,
+        config: ReserveConfig,
+        oracle_sources: Vec<OracleSource>,
+    ) -> Result<()> {
+        instructions::process_init_reserve(ctx, config, oracle_sources)
+    }
 
-#[derive(Accounts)]
-pub struct Liquidate<'info> {
-    #[account(mut)]
-    pub position: Account<'info, Position>,
-    pub liquidator: Signer<'info>,
-}
+    pub fn update_reserve_config(
+        ctx: Context<UpdateReserveConfig>,
+        config: ReserveConfig,
+    ) -> Result<()> {
+        instructions::process_update_reserve_config(ctx, config)
+    }
 
-#[account]
-pub struct Position {
-    pub owner: Pubkey,
-    pub collateral_amount: u64,
-    pub borrowed_amount: u64,
-}
+    pub fn set_market_pauses(ctx: Context<SetMarketPauses>, pauses: MarketPauses) -> Result<()> {
+        instructions::process_set_market_pauses(ctx, pauses)
+    }
+
+    pub fn set_circuit_breaker(
+        ctx: Context<SetCircuitBreaker>,
+        max_price_move_bps: u16,
+        window_slots: u64,
+    ) -> Result<()> {
+        instructions::process_set_circuit_breaker(ctx, max_price_move_bps, window_slots)
+    }
+
+    pub fn init_governance(
+        ctx: Context<InitGovernance>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        timelock_slots: u64,
+    ) -> Result<()> {
+        instructions::process_init_governance(ctx, signers, threshold, timelock_slots)
+    }
+
+    pub fn propose_risk_parameters(
+        ctx: Context<ProposeRiskParameters>,
+        risk_parameters: RiskParameters,
+    ) -> Result<()> {
+        instructions::process_propose_risk_parameters(ctx, risk_parameters)
+    }
+
+    pub fn approve_risk_proposal(ctx: Context<ApproveRiskProposal>) -> Result<()> {
+        instructions::process_approve_risk_proposal(ctx)
+    }
+
+    pub fn execute_risk_proposal(ctx: Context<ExecuteRiskProposal>) -> Result<()> {
+        instructions::process_execute_risk_proposal(ctx)
+    }
+
+    pub fn fund_reserve(ctx: Context<FundReserve>, amount: u64) -> Result<u64> {
+        instructions::process_fund_reserve(ctx, amount)
+    }
+
+    pub fn init_obligation(ctx: Context<InitObligation>) -> Result<()> {
+        instructions::process_init_obligation(ctx)
+    }
+
+    pub fn create_position(
+        ctx: Context<CreatePosition>,
+        collateral_amount: u64,
+        borrow_amount: u64,
+    ) -> Result<()> {
+        instructions::process_create_position(ctx, collateral_amount, borrow_amount)
+    }
+
+    pub fn create_risky_position(ctx: Context<CreateRiskyPosition>) -> Result<()> {
+        instructions::process_create_risky_position(ctx)
+    }
+
+    pub fn refresh_reserve(ctx: Context<RefreshReserve>) -> Result<()> {
+        instructions::process_refresh_reserve(ctx)
+    }
+
+    pub fn refresh_obligation(ctx: Context<RefreshObligation>) -> Result<()> {
+        instructions::process_refresh_obligation(ctx)
+    }
 
-#[error_code]
-pub enum LiquidationError {
-    #[msg("Position is not liquidatable")]
-    NotLiquidatable
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        instructions::process_deposit(ctx, amount)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        instructions::process_withdraw(ctx, amount)
+    }
+
+    pub fn borrow(ctx: Context<Borrow>, amount: u64) -> Result<()> {
+        instructions::process_borrow(ctx, amount)
+    }
+
+    pub fn repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
+        instructions::process_repay(ctx, amount)
+    }
+
+    pub fn close_position<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClosePosition<'info>>,
+    ) -> Result<()> {
+        instructions::process_close_position(ctx)
+    }
+
+    pub fn close_empty_position(ctx: Context<CloseEmptyPosition>) -> Result<u64> {
+        instructions::process_close_empty_position(ctx)
+    }
+
+    pub fn liquidate(ctx: Context<Liquidate>, repay_amount: u64) -> Result<u64> {
+        instructions::process_liquidate(ctx, repay_amount)
+    }
+
+    pub fn write_off_bad_debt(ctx: Context<WriteOffBadDebt>) -> Result<()> {
+        instructions::process_write_off_bad_debt(ctx)
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashBorrow>, amount: u64) -> Result<()> {
+        instructions::process_flash_borrow(ctx, amount)
+    }
+
+    pub fn flash_repay(
+        ctx: Context<FlashRepay>,
+        amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Result<()> {
+        instructions::process_flash_repay(ctx, amount, borrow_instruction_index)
+    }
+
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        instructions::process_withdraw_protocol_fees(ctx, amount)
+    }
+
+    pub fn init_insurance_fund(
+        ctx: Context<InitInsuranceFund>,
+        unstake_cooldown_slots: u64,
+    ) -> Result<()> {
+        instructions::process_init_insurance_fund(ctx, unstake_cooldown_slots)
+    }
+
+    pub fn stake_insurance(ctx: Context<StakeInsurance>, amount: u64) -> Result<()> {
+        instructions::process_stake_insurance(ctx, amount)
+    }
+
+    pub fn request_insurance_unstake(
+        ctx: Context<RequestInsuranceUnstake>,
+        shares: u64,
+    ) -> Result<()> {
+        instructions::process_request_insurance_unstake(ctx, shares)
+    }
+
+    pub fn unstake_insurance(ctx: Context<UnstakeInsurance>) -> Result<u64> {
+        instructions::process_unstake_insurance(ctx)
+    }
+
+    pub fn collect_insurance_fees(ctx: Context<CollectInsuranceFees>) -> Result<u64> {
+        instructions::process_collect_insurance_fees(ctx)
+    }
 }
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::WAD;
+
+/// `WAD` per percent.
+pub const PERCENT_SCALER: u128 = WAD / 100;
+
+/// `WAD` per basis point.
+pub const BPS_SCALER: u128 = WAD / 10_000;
+
+pub trait TryAdd: Sized {
+    fn try_add(self, rhs: Self) -> Result<Self>;
+}
+
+pub trait TrySub: Sized {
+    fn try_sub(self, rhs: Self) -> Result<Self>;
+}
+
+pub trait TryMul<Rhs>: Sized {
+    fn try_mul(self, rhs: Rhs) -> Result<Self>;
+}
+
+pub trait TryDiv<Rhs>: Sized {
+    fn try_div(self, rhs: Rhs) -> Result<Self>;
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+
+use anchor_lang::prelude::*;
+
+use crate::constants::WAD;
+use crate::error::LiquidationError;
+use crate::math::{Rate, TryAdd, TryDiv, TryMul, TrySub, BPS_SCALER, PERCENT_SCALER};
+
+// The generated code needs `core::result::Result`, not the Anchor prelude's.
+#[allow(clippy::all)]
+mod uint_type {
+    uint::construct_uint! {
+        pub struct U192(3);
+    }
+}
+pub use uint_type::U192;
+
+/// Token amounts and USD values, precise to 18 decimals. Account fields keep these as
+/// their `u128` scaled value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Decimal(pub U192);
+
+impl Decimal {
+    pub fn one() -> Self {
+        Self(Self::wad())
+    }
+
+    pub fn zero() -> Self {
+        Self(U192::zero())
+    }
+
+    fn wad() -> U192 {
+        U192::from(WAD)
+    }
+
+    pub fn from_percent(percent: u8) -> Self {
+        Self(U192::from(percent as u128 * PERCENT_SCALER))
+    }
+
+    pub fn from_bps(bps: u16) -> Self {
+        Self(U192::from(bps as u128 * BPS_SCALER))
+    }
+
+    /// Wraps a value already scaled by `WAD`, such as a stored `*_wads` field.
+    pub fn from_scaled_val(scaled_val: u128) -> Self {
+        Self(U192::from(scaled_val))
+    }
+
+    /// The value scaled by `WAD`, for storing in an account.
+    pub fn to_scaled_val(&self) -> Result<u128> {
+        u128::try_from(self.0).map_err(|_| error!(LiquidationError::MathOverflow))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Difference clamped at zero.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Rounds down to a whole number, for amounts the protocol pays out.
+    pub fn try_floor_u64(&self) -> Result<u64> {
+        u64::try_from(self.0 / Self::wad()).map_err(|_| error!(LiquidationError::MathOverflow))
+    }
+
+    /// Rounds up to a whole number, for amounts owed to the protocol.
+    pub fn try_ceil_u64(&self) -> Result<u64> {
+        let ceiling = self
+            .0
+            .checked_add(Self::wad() - 1)
+            .ok_or(LiquidationError::MathOverflow)?
+            / Self::wad();
+        u64::try_from(ceiling).map_err(|_| error!(LiquidationError::MathOverflow))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut scaled_val = self.0.to_string();
+        if scaled_val.len() <= 18 {
+            scaled_val.insert_str(0, &"0".repeat(18 - scaled_val.len()));
+            scaled_val.insert_str(0, "0.");
+        } else {
+            scaled_val.insert(scaled_val.len() - 18, '.');
+        }
+        f.write_str(&scaled_val)
+    }
+}
+
+impl From<u64> for Decimal {
+    fn from(val: u64) -> Self {
+        Self(Self::wad() * U192::from(val))
+    }
+}
+
+impl From<Rate> for Decimal {
+    fn from(val: Rate) -> Self {
+        Self(U192::from(val.to_scaled_val()))
+    }
+}
+
+impl TryAdd for Decimal {
+    fn try_add(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_add(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TrySub for Decimal {
+    fn try_sub(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_sub(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryDiv<u64> for Decimal {
+    fn try_div(self, rhs: u64) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_div(U192::from(rhs))
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryDiv<Rate> for Decimal {
+    fn try_div(self, rhs: Rate) -> Result<Self> {
+        self.try_div(Self::from(rhs))
+    }
+}
+
+impl TryDiv<Decimal> for Decimal {
+    fn try_div(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(LiquidationError::MathOverflow)?
+                .checked_div(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryMul<u64> for Decimal {
+    fn try_mul(self, rhs: u64) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_mul(U192::from(rhs))
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryMul<Rate> for Decimal {
+    fn try_mul(self, rhs: Rate) -> Result<Self> {
+        self.try_mul(Self::from(rhs))
+    }
+}
+
+impl TryMul<Decimal> for Decimal {
+    fn try_mul(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_mul(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?
+                / Self::wad(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn math_overflow() -> Error {
+        LiquidationError::MathOverflow.into()
+    }
+
+    #[test]
+    fn rounds_to_whole_amounts() {
+        let amount = Decimal::from(7u64)
+            .try_add(Decimal::from_scaled_val(1))
+            .unwrap();
+        assert_eq!(amount.try_floor_u64().unwrap(), 7);
+        assert_eq!(amount.try_ceil_u64().unwrap(), 8);
+
+        let whole = Decimal::from(7u64);
+        assert_eq!(whole.try_floor_u64().unwrap(), 7);
+        assert_eq!(whole.try_ceil_u64().unwrap(), 7);
+    }
+
+    #[test]
+    fn rejects_amounts_too_large_for_u64() {
+        let too_large = Decimal::from(u64::MAX).try_add(Decimal::one()).unwrap();
+        assert_eq!(too_large.try_floor_u64().unwrap_err(), math_overflow());
+        assert_eq!(too_large.try_ceil_u64().unwrap_err(), math_overflow());
+        assert_eq!(
+            Decimal::from(u64::MAX)
+                .try_add(Decimal::from_scaled_val(1))
+                .unwrap()
+                .try_ceil_u64()
+                .unwrap_err(),
+            math_overflow()
+        );
+        assert_eq!(
+            Decimal(U192::MAX).to_scaled_val().unwrap_err(),
+            math_overflow()
+        );
+    }
+
+    #[test]
+    fn overflows_into_math_overflow() {
+        let max = Decimal(U192::MAX);
+        assert_eq!(max.try_add(Decimal::one()).unwrap_err(), math_overflow());
+        assert_eq!(
+            Decimal::zero().try_sub(Decimal::one()).unwrap_err(),
+            math_overflow()
+        );
+        assert_eq!(max.try_mul(2u64).unwrap_err(), math_overflow());
+        assert_eq!(
+            max.try_mul(Decimal::from(2u64)).unwrap_err(),
+            math_overflow()
+        );
+        assert_eq!(max.try_div(Decimal::one()).unwrap_err(), math_overflow());
+        assert_eq!(
+            Decimal::one().try_div(Decimal::zero()).unwrap_err(),
+            math_overflow()
+        );
+        assert_eq!(Decimal::one().try_div(0u64).unwrap_err(), math_overflow());
+    }
+
+    #[test]
+    fn multiplies_and_divides_at_wad_precision() {
+        let price = Decimal::from(3u64).try_div(2u64).unwrap();
+        assert_eq!(price, Decimal::from_scaled_val(1_500_000_000_000_000_000));
+        assert_eq!(
+            Decimal::from(10u64).try_mul(price).unwrap(),
+            Decimal::from(15u64)
+        );
+        assert_eq!(
+            Decimal::from(15u64).try_div(price).unwrap(),
+            Decimal::from(10u64)
+        );
+        assert_eq!(
+            Decimal::from(200u64)
+                .try_mul(Rate::from_percent(5))
+                .unwrap(),
+            Decimal::from(10u64)
+        );
+        assert_eq!(
+            Decimal::from(10u64)
+                .try_div(Rate::from_percent(50))
+                .unwrap(),
+            Decimal::from(20u64)
+        );
+
+        // Quotients truncate at 18 decimals
+        let third = Decimal::one().try_div(Decimal::from(3u64)).unwrap();
+        assert_eq!(third, Decimal::from_scaled_val(333_333_333_333_333_333));
+        assert_eq!(third.to_string(), "0.333333333333333333");
+    }
+}
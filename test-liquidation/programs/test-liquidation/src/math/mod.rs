@@ -0,0 +1,13 @@
+//! Fixed-point math for token values and rates, after Solend's `math` module.
+//!
+//! Both types hold values scaled by `WAD`. Products and quotients truncate at 18
+//! decimals; callers choose the rounding direction when converting back to token
+//! amounts, rounding debts up and payouts down so rounding always favors the protocol.
+
+mod common;
+mod decimal;
+mod rate;
+
+pub use common::*;
+pub use decimal::*;
+pub use rate::*;
@@ -0,0 +1,209 @@
+use std::fmt;
+
+use anchor_lang::prelude::*;
+
+use crate::constants::WAD;
+use crate::error::LiquidationError;
+use crate::math::{Decimal, TryAdd, TryDiv, TryMul, TrySub, BPS_SCALER, PERCENT_SCALER};
+
+// Generated apart from the Anchor prelude, like `U192` in `decimal.rs`.
+#[allow(clippy::all)]
+mod uint_type {
+    uint::construct_uint! {
+        pub struct U128(2);
+    }
+}
+pub use uint_type::U128;
+
+/// Ratios such as interest rates and utilization, precise to 18 decimals. Smaller
+/// than `Decimal`, so it is cheaper to compound.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord)]
+pub struct Rate(pub U128);
+
+impl Rate {
+    pub fn one() -> Self {
+        Self(Self::wad())
+    }
+
+    pub fn zero() -> Self {
+        Self(U128::zero())
+    }
+
+    fn wad() -> U128 {
+        U128::from(WAD)
+    }
+
+    pub fn from_percent(percent: u8) -> Self {
+        Self(U128::from(percent as u128 * PERCENT_SCALER))
+    }
+
+    pub fn from_bps(bps: u16) -> Self {
+        Self(U128::from(bps as u128 * BPS_SCALER))
+    }
+
+    pub fn from_scaled_val(scaled_val: u128) -> Self {
+        Self(U128::from(scaled_val))
+    }
+
+    pub fn to_scaled_val(&self) -> u128 {
+        self.0.as_u128()
+    }
+
+    /// Raises the rate to an integer power by repeated squaring.
+    pub fn try_pow(&self, mut exp: u64) -> Result<Self> {
+        let mut base = *self;
+        let mut ret = if exp & 1 == 1 { base } else { Self::one() };
+
+        while exp > 1 {
+            exp >>= 1;
+            base = base.try_mul(base)?;
+            if exp & 1 == 1 {
+                ret = ret.try_mul(base)?;
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Decimal::from(*self), f)
+    }
+}
+
+impl TryFrom<Decimal> for Rate {
+    type Error = Error;
+
+    fn try_from(decimal: Decimal) -> Result<Self> {
+        Ok(Self(U128::from(decimal.to_scaled_val()?)))
+    }
+}
+
+impl TryAdd for Rate {
+    fn try_add(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_add(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TrySub for Rate {
+    fn try_sub(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_sub(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryDiv<u64> for Rate {
+    fn try_div(self, rhs: u64) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_div(U128::from(rhs))
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryDiv<Rate> for Rate {
+    fn try_div(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(LiquidationError::MathOverflow)?
+                .checked_div(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryMul<u64> for Rate {
+    fn try_mul(self, rhs: u64) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_mul(U128::from(rhs))
+                .ok_or(LiquidationError::MathOverflow)?,
+        ))
+    }
+}
+
+impl TryMul<Rate> for Rate {
+    fn try_mul(self, rhs: Self) -> Result<Self> {
+        Ok(Self(
+            self.0
+                .checked_mul(rhs.0)
+                .ok_or(LiquidationError::MathOverflow)?
+                / Self::wad(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn math_overflow() -> Error {
+        LiquidationError::MathOverflow.into()
+    }
+
+    #[test]
+    fn raises_to_integer_powers() {
+        let rate = Rate::from_percent(110);
+        assert_eq!(rate.try_pow(0).unwrap(), Rate::one());
+        assert_eq!(rate.try_pow(1).unwrap(), rate);
+        assert_eq!(rate.try_pow(2).unwrap(), Rate::from_percent(121));
+        assert_eq!(
+            rate.try_pow(3).unwrap(),
+            Rate::from_scaled_val(1_331_000_000_000_000_000)
+        );
+        assert_eq!(
+            Rate::from_percent(200).try_pow(4).unwrap().to_string(),
+            "16.000000000000000000"
+        );
+        assert_eq!(
+            Rate::from_percent(200).try_pow(128).unwrap_err(),
+            math_overflow()
+        );
+    }
+
+    #[test]
+    fn multiplies_and_divides_at_wad_precision() {
+        let half = Rate::from_percent(50);
+        assert_eq!(half.try_mul(half).unwrap(), Rate::from_percent(25));
+        assert_eq!(
+            half.try_div(Rate::from_percent(25)).unwrap(),
+            Rate::from_percent(200)
+        );
+        assert_eq!(half.try_mul(3u64).unwrap(), Rate::from_percent(150));
+        assert_eq!(half.try_div(5u64).unwrap(), Rate::from_percent(10));
+        assert_eq!(
+            Rate::from_bps(250),
+            Rate::from_scaled_val(25_000_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn overflows_into_math_overflow() {
+        let max = Rate(U128::MAX);
+        assert_eq!(max.try_add(Rate::one()).unwrap_err(), math_overflow());
+        assert_eq!(
+            Rate::zero().try_sub(Rate::one()).unwrap_err(),
+            math_overflow()
+        );
+        assert_eq!(max.try_mul(max).unwrap_err(), math_overflow());
+        assert_eq!(max.try_div(Rate::one()).unwrap_err(), math_overflow());
+        assert_eq!(
+            Rate::one().try_div(Rate::zero()).unwrap_err(),
+            math_overflow()
+        );
+        assert_eq!(
+            Rate::try_from(Decimal::from(u64::MAX).try_mul(u64::MAX).unwrap()).unwrap_err(),
+            math_overflow()
+        );
+    }
+}
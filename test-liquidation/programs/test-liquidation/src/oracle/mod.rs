@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use mock_oracle::PriceFeed;
+
+use crate::constants::MAX_RESERVE_ORACLES;
+use crate::error::LiquidationError;
+use crate::events::ReservePriceUpdated;
+use crate::math::{Decimal, TryDiv, TryMul, TrySub};
+use crate::state::{OracleSource, Reserve, ReserveConfig};
+
+pub mod pyth;
+pub mod switchboard;
+
+use pyth::{PriceUpdateV2, PYTH_RECEIVER_PROGRAM_ID};
+use switchboard::{get_aggregator_price, SWITCHBOARD_V2_PROGRAM_ID};
+
+/// A price quote as `price * 10^expo`, normalized from any supported oracle layout.
+#[derive(Clone, Copy)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: i32,
+    pub conf: u64,
+    pub publish_slot: u64,
+    pub publish_time: i64,
+}
+
+impl From<&PriceFeed> for OraclePrice {
+    fn from(feed: &PriceFeed) -> Self {
+        Self {
+            price: feed.price,
+            expo: feed.expo,
+            conf: feed.conf,
+            publish_slot: feed.publish_slot,
+            publish_time: feed.publish_time,
+        }
+    }
+}
+
+/// Converts a `price * 10^expo` quote into a decimal.
+pub fn price_to_decimal(price: i64, expo: i32) -> Result<Decimal> {
+    require!(price > 0, LiquidationError::InvalidOraclePrice);
+
+    let price = Decimal::from(price as u64);
+    let scale = 10u64
+        .checked_pow(expo.unsigned_abs())
+        .ok_or(LiquidationError::MathOverflow)?;
+    if expo >= 0 {
+        price.try_mul(scale)
+    } else {
+        price.try_div(scale)
+    }
+}
+
+/// Which of a reserve's oracles its accepted price came from.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PriceSource {
+    Primary,
+    Secondary,
+    Tertiary,
+    /// Median of all three oracles.
+    Median,
+}
+
+impl PriceSource {
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => Self::Primary,
+            1 => Self::Secondary,
+            _ => Self::Tertiary,
+        }
+    }
+}
+
+/// Checks an oracle quote against the reserve's freshness and confidence limits,
+/// returning its price.
+pub fn validate_price(
+    feed: &OraclePrice,
+    config: &ReserveConfig,
+    clock: &Clock,
+) -> Result<Decimal> {
+    require!(
+        clock.slot.saturating_sub(feed.publish_slot) <= config.max_oracle_staleness_slots,
+        LiquidationError::StaleOracle
+    );
+    require!(
+        clock
+            .unix_timestamp
+            .saturating_sub(feed.publish_time)
+            .max(0) as u64
+            <= config.max_oracle_staleness_seconds,
+        LiquidationError::StaleOracle
+    );
+    require!(feed.price > 0, LiquidationError::InvalidOraclePrice);
+    require!(
+        feed.conf as u128 * 10_000 <= feed.price as u128 * config.max_oracle_confidence_bps as u128,
+        LiquidationError::OracleConfidenceTooWide
+    );
+
+    price_to_decimal(feed.price, feed.expo)
+}
+
+/// Rejects a price that moved further than the configured band from the last price
+/// the reserve accepted, unless it has none or accepted it longer ago than the
+/// configured window.
+fn check_price_deviation(price: Decimal, reserve: &Reserve, slot: u64) -> Result<()> {
+    let config = &reserve.config;
+    let last_price = Decimal::from_scaled_val(reserve.liquidity.market_price);
+    let last_price_age = slot.saturating_sub(reserve.liquidity.market_price_slot);
+    if config.max_price_deviation_bps > 0
+        && !last_price.is_zero()
+        && last_price_age <= config.price_deviation_window_slots
+    {
+        let deviation = price.max(last_price).try_sub(price.min(last_price))?;
+        require!(
+            deviation.try_div(last_price)? <= Decimal::from_bps(config.max_price_deviation_bps),
+            LiquidationError::OraclePriceDeviation
+        );
+    }
+
+    Ok(())
+}
+
+/// Decodes an oracle account with the layout of its configured source.
+fn read_oracle_price(
+    source: OracleSource,
+    config: &ReserveConfig,
+    oracle: &AccountInfo,
+) -> Result<OraclePrice> {
+    let data = oracle.try_borrow_data()?;
+    match source {
+        OracleSource::Mock => {
+            require_keys_eq!(
+                *oracle.owner,
+                mock_oracle::ID,
+                LiquidationError::InvalidOracle
+            );
+            Ok(OraclePrice::from(&PriceFeed::try_deserialize(
+                &mut &data[..],
+            )?))
+        }
+        OracleSource::Pyth { feed_id } => {
+            require_keys_eq!(
+                *oracle.owner,
+                PYTH_RECEIVER_PROGRAM_ID,
+                LiquidationError::InvalidOracle
+            );
+            PriceUpdateV2::try_from_account_data(&data)?.get_price(&feed_id, config.use_ema_price)
+        }
+        OracleSource::Switchboard => {
+            require_keys_eq!(
+                *oracle.owner,
+                SWITCHBOARD_V2_PROGRAM_ID,
+                LiquidationError::InvalidOracle
+            );
+            get_aggregator_price(&data)
+        }
+    }
+}
+
+/// Reads the `WAD`-scaled USD price of a reserve from its oracle accounts, given in
+/// the order they are configured. With three healthy oracles the median is used;
+/// otherwise the first oracle that is fresh and within its confidence bound wins.
+pub fn get_market_price(
+    reserve_key: Pubkey,
+    reserve: &Reserve,
+    oracles: &[AccountInfo],
+) -> Result<u128> {
+    let configured = reserve.liquidity.oracles();
+    require!(
+        oracles.len() == configured.len(),
+        LiquidationError::InvalidOracle
+    );
+
+    let clock = Clock::get()?;
+    let mut quotes = Vec::with_capacity(oracles.len());
+    for (oracle, info) in configured.iter().zip(oracles) {
+        require_keys_eq!(info.key(), oracle.pubkey, LiquidationError::InvalidOracle);
+        let quote = read_oracle_price(oracle.source, &reserve.config, info)?;
+        quotes.push(validate_price(&quote, &reserve.config, &clock));
+    }
+
+    let (source, price) = if quotes.len() == MAX_RESERVE_ORACLES && quotes.iter().all(Result::is_ok)
+    {
+        let mut prices: Vec<Decimal> = quotes.into_iter().map(Result::unwrap).collect();
+        prices.sort_unstable();
+        (PriceSource::Median, prices[MAX_RESERVE_ORACLES / 2])
+    } else {
+        match quotes.iter().position(Result::is_ok) {
+            Some(index) => (
+                PriceSource::from_index(index),
+                *quotes[index].as_ref().unwrap(),
+            ),
+            // Every oracle failed; surface the primary's reason.
+            None => return Err(quotes.swap_remove(0).unwrap_err()),
+        }
+    };
+    check_price_deviation(price, reserve, clock.slot)?;
+
+    let market_price = price.to_scaled_val()?;
+    emit!(ReservePriceUpdated {
+        reserve: reserve_key,
+        source,
+        market_price,
+    });
+
+    Ok(market_price)
+}
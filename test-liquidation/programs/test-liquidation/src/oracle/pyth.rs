@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+use super::OraclePrice;
+use crate::error::LiquidationError;
+
+/// Pyth's Solana receiver program, which owns every `PriceUpdateV2` account.
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor discriminator of `PriceUpdateV2`, i.e. `sha256("account:PriceUpdateV2")[..8]`.
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// How many Wormhole guardian signatures backed a posted update.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// Mirror of the Pyth pull-oracle account layout, decoded by hand so the program
+/// does not depend on the receiver SDK.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= PRICE_UPDATE_V2_DISCRIMINATOR.len()
+                && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        Self::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+
+    /// Returns the spot or EMA price for `feed_id`, rejecting updates that were
+    /// not verified by the full guardian set.
+    pub fn get_price(&self, feed_id: &[u8; 32], use_ema_price: bool) -> Result<OraclePrice> {
+        require!(
+            self.verification_level == VerificationLevel::Full,
+            LiquidationError::InvalidOracle
+        );
+        let message = &self.price_message;
+        require!(
+            message.feed_id == *feed_id,
+            LiquidationError::InvalidOracleFeedId
+        );
+
+        let (price, conf) = if use_ema_price {
+            (message.ema_price, message.ema_conf)
+        } else {
+            (message.price, message.conf)
+        };
+        Ok(OraclePrice {
+            price,
+            expo: message.exponent,
+            conf,
+            publish_slot: self.posted_slot,
+            publish_time: message.publish_time,
+        })
+    }
+}
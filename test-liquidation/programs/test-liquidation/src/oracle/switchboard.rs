@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+use super::OraclePrice;
+use crate::error::LiquidationError;
+
+/// Switchboard V2 program, which owns every aggregator account.
+pub const SWITCHBOARD_V2_PROGRAM_ID: Pubkey =
+    pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+/// Anchor discriminator of `AggregatorAccountData`.
+const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+/// Byte offsets into the packed `AggregatorAccountData` layout, discriminator
+/// included, of the `latest_confirmed_round` fields read below.
+const ROUND_OPEN_SLOT_OFFSET: usize = 350;
+const ROUND_OPEN_TIMESTAMP_OFFSET: usize = 358;
+const RESULT_OFFSET: usize = 366;
+const STD_DEVIATION_OFFSET: usize = 386;
+
+/// Size of a packed `SwitchboardDecimal`: an `i128` mantissa and a `u32` scale.
+const DECIMAL_LEN: usize = 20;
+
+/// Largest scale Switchboard writes, that of a `rust_decimal::Decimal`.
+const MAX_SCALE: u32 = 28;
+
+/// A Switchboard value of `mantissa * 10^-scale`.
+#[derive(Clone, Copy)]
+struct SwitchboardDecimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl SwitchboardDecimal {
+    fn read(data: &[u8], offset: usize) -> Self {
+        Self {
+            mantissa: i128::from_le_bytes(data[offset..offset + 16].try_into().unwrap()),
+            scale: u32::from_le_bytes(data[offset + 16..offset + 20].try_into().unwrap()),
+        }
+    }
+
+    /// Mantissa re-expressed with `scale` decimals, truncating extra precision.
+    fn mantissa_at_scale(&self, scale: u32) -> Option<i128> {
+        if scale >= self.scale {
+            self.mantissa
+                .checked_mul(10i128.checked_pow(scale - self.scale)?)
+        } else {
+            Some(self.mantissa / 10i128.checked_pow(self.scale - scale)?)
+        }
+    }
+}
+
+/// Reads the latest confirmed round of a Switchboard V2 aggregator, using its
+/// standard deviation as the confidence interval.
+pub fn get_aggregator_price(data: &[u8]) -> Result<OraclePrice> {
+    require!(
+        data.len() >= STD_DEVIATION_OFFSET + DECIMAL_LEN,
+        ErrorCode::AccountDidNotDeserialize
+    );
+    require!(
+        data[..8] == AGGREGATOR_DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    let result = SwitchboardDecimal::read(data, RESULT_OFFSET);
+    let std_deviation = SwitchboardDecimal::read(data, STD_DEVIATION_OFFSET);
+    require!(
+        result.scale <= MAX_SCALE,
+        LiquidationError::InvalidOraclePrice
+    );
+    // Drop decimals until the mantissa fits the `i64` price, as results past about
+    // nine at eighteen decimals do not
+    let mut scale = result.scale;
+    let price = loop {
+        if let Some(price) = result
+            .mantissa_at_scale(scale)
+            .and_then(|mantissa| i64::try_from(mantissa).ok())
+        {
+            break price;
+        }
+        require!(scale > 0, LiquidationError::InvalidOraclePrice);
+        scale -= 1;
+    };
+    let conf = std_deviation
+        .mantissa_at_scale(scale)
+        .and_then(|conf| u64::try_from(conf.unsigned_abs()).ok())
+        .ok_or(LiquidationError::InvalidOraclePrice)?;
+
+    Ok(OraclePrice {
+        price,
+        expo: -(scale as i32),
+        conf,
+        publish_slot: u64::from_le_bytes(
+            data[ROUND_OPEN_SLOT_OFFSET..ROUND_OPEN_SLOT_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        ),
+        publish_time: i64::from_le_bytes(
+            data[ROUND_OPEN_TIMESTAMP_OFFSET..ROUND_OPEN_TIMESTAMP_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        ),
+    })
+}
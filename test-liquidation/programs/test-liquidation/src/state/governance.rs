@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_GOVERNANCE_SIGNERS;
+use crate::state::RiskParameters;
+
+/// The M-of-N signer set that approves risk parameter changes in a lending market.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct Governance {
+    #[max_len(MAX_GOVERNANCE_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    /// Approvals a proposal needs before its timelock starts.
+    pub threshold: u8,
+    /// Slots between a proposal reaching the threshold and it becoming executable.
+    pub timelock_slots: u64,
+    /// Id the next proposal's address is derived with.
+    pub next_proposal_id: u64,
+}
+
+impl Governance {
+    pub fn is_initialized(&self) -> bool {
+        !self.signers.is_empty()
+    }
+
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signers.contains(key)
+    }
+}
+
+/// A proposed change to one reserve's risk parameters.
+#[account]
+#[derive(InitSpace)]
+pub struct RiskProposal {
+    pub lending_market: Pubkey,
+    pub reserve: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub risk_parameters: RiskParameters,
+    /// Governance signers that approved, the proposer first.
+    #[max_len(MAX_GOVERNANCE_SIGNERS)]
+    pub approvals: Vec<Pubkey>,
+    /// Slot the proposal reached the approval threshold; its timelock runs from here.
+    pub approved_slot: Option<u64>,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl RiskProposal {
+    /// Records `signer`'s approval once, starting the timelock when the approvals
+    /// reach `threshold`.
+    pub fn approve(&mut self, signer: Pubkey, threshold: u8, slot: u64) {
+        if !self.approvals.contains(&signer) {
+            self.approvals.push(signer);
+        }
+        if self.approved_slot.is_none() && self.approvals.len() >= threshold as usize {
+            self.approved_slot = Some(slot);
+        }
+    }
+}
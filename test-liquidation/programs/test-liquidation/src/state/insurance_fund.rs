@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+/// A lending market's insurance fund. Its funds sit in one insurance vault per
+/// reserve, since every reserve holds a different mint; this account carries the
+/// rules shared by all of them.
+#[account]
+#[derive(InitSpace)]
+pub struct InsuranceFund {
+    pub lending_market: Pubkey,
+    /// Slots a staker must wait between requesting an unstake and withdrawing, so
+    /// stakers cannot run ahead of bad debt they see coming.
+    pub unstake_cooldown_slots: u64,
+    pub bump: u8,
+}
+
+/// One staker's share of a reserve's insurance vault.
+#[account]
+#[derive(InitSpace)]
+pub struct InsuranceStake {
+    pub reserve: Pubkey,
+    pub staker: Pubkey,
+    /// Shares that can be requested for unstaking.
+    pub shares: u64,
+    /// Shares waiting out the cooldown; they still absorb bad debt until withdrawn.
+    pub unstaking_shares: u64,
+    /// Slot of the latest unstake request.
+    pub unstake_requested_slot: u64,
+    /// The reserve's share epoch the shares were issued in.
+    pub share_epoch: u64,
+    pub bump: u8,
+}
+
+impl InsuranceStake {
+    /// Drops shares the reserve has retired since they were issued.
+    pub fn sync_share_epoch(&mut self, share_epoch: u64) {
+        if self.share_epoch != share_epoch {
+            self.shares = 0;
+            self.unstaking_shares = 0;
+            self.share_epoch = share_epoch;
+        }
+    }
+}
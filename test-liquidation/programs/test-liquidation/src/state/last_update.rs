@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct LastUpdate {
+    pub slot: u64,
+    pub stale: bool,
+}
+
+impl LastUpdate {
+    pub fn update_slot(&mut self, slot: u64) {
+        self.slot = slot;
+        self.stale = false;
+    }
+
+    /// Flags cached values as out of date until the next refresh.
+    pub fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
+    /// Whether the account was not refreshed in `slot` or has changed since.
+    pub fn is_stale(&self, slot: u64) -> bool {
+        self.stale || self.slot != slot
+    }
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::error::LiquidationError;
+use crate::state::Governance;
+
+#[account]
+#[derive(InitSpace)]
+pub struct LendingMarket {
+    pub version: u8,
+    /// Bump of the lending market authority PDA that signs for reserve vaults.
+    pub bump_seed: u8,
+    /// Authority allowed to add reserves, pause the market and change reserve settings
+    /// other than risk parameters.
+    pub owner: Pubkey,
+    /// Operations the owner has paused.
+    pub pauses: MarketPauses,
+    pub circuit_breaker: CircuitBreaker,
+    pub governance: Governance,
+}
+
+/// Operations paused by the market owner.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct MarketPauses {
+    /// Pauses every operation that moves funds in or out of the market's reserves,
+    /// except repaying flash loans.
+    pub all: bool,
+    pub borrows: bool,
+    pub withdrawals: bool,
+    pub liquidations: bool,
+}
+
+/// Halts borrowing across the market once a reserve's oracle price moves too far
+/// within a window of slots.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct CircuitBreaker {
+    /// Largest price move, in basis points, allowed within `window_slots`; zero
+    /// disables the breaker.
+    pub max_price_move_bps: u16,
+    pub window_slots: u64,
+    /// Set when the breaker trips; borrowing stays halted until the owner resets it.
+    pub tripped: bool,
+}
+
+impl LendingMarket {
+    pub fn check_not_paused(&self) -> Result<()> {
+        require!(!self.pauses.all, LiquidationError::MarketPaused);
+        Ok(())
+    }
+
+    pub fn check_borrows_allowed(&self) -> Result<()> {
+        self.check_not_paused()?;
+        require!(!self.pauses.borrows, LiquidationError::BorrowsPaused);
+        require!(
+            !self.circuit_breaker.tripped,
+            LiquidationError::CircuitBreakerTripped
+        );
+        Ok(())
+    }
+
+    pub fn check_withdrawals_allowed(&self) -> Result<()> {
+        self.check_not_paused()?;
+        require!(
+            !self.pauses.withdrawals,
+            LiquidationError::WithdrawalsPaused
+        );
+        Ok(())
+    }
+
+    pub fn check_liquidations_allowed(&self) -> Result<()> {
+        self.check_not_paused()?;
+        require!(
+            !self.pauses.liquidations,
+            LiquidationError::LiquidationsPaused
+        );
+        Ok(())
+    }
+}
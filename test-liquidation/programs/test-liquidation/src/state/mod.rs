@@ -0,0 +1,15 @@
+pub mod governance;
+pub mod insurance_fund;
+pub mod last_update;
+pub mod lending_market;
+pub mod obligation;
+pub mod obligation_counter;
+pub mod reserve;
+
+pub use governance::*;
+pub use insurance_fund::*;
+pub use last_update::*;
+pub use lending_market::*;
+pub use obligation::*;
+pub use obligation_counter::*;
+pub use reserve::*;
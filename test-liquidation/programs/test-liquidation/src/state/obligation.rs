@@ -0,0 +1,443 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_OBLIGATION_LEGS, PROGRAM_VERSION};
+use crate::error::LiquidationError;
+use crate::math::{Decimal, Rate, TryAdd, TryDiv, TryMul};
+use crate::state::{LastUpdate, Reserve, ReserveConfig};
+
+/// A borrower's deposits and borrows across the reserves of one lending market,
+/// laid out after Solend's obligation. Values are USD scaled by `WAD`.
+#[account]
+#[derive(InitSpace)]
+pub struct Obligation {
+    pub version: u8,
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    /// Position index the obligation address is derived with.
+    pub index: u64,
+    pub bump: u8,
+    /// Set once a liquidation has seized collateral, after which an emptied
+    /// obligation may be closed by anyone.
+    pub liquidated: bool,
+    #[max_len(MAX_OBLIGATION_LEGS)]
+    pub deposits: Vec<ObligationCollateral>,
+    #[max_len(MAX_OBLIGATION_LEGS)]
+    pub borrows: Vec<ObligationLiquidity>,
+    pub deposited_value: u128,
+    pub borrowed_value: u128,
+    /// Borrow value the deposits support at each reserve's loan-to-value ratio.
+    pub allowed_borrow_value: u128,
+    /// Borrow value above which the obligation can be liquidated.
+    pub unhealthy_borrow_value: u128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ObligationCollateral {
+    pub deposit_reserve: Pubkey,
+    pub deposited_amount: u64,
+    pub market_value: u128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ObligationLiquidity {
+    pub borrow_reserve: Pubkey,
+    /// Reserve's cumulative borrow rate when interest was last applied to this leg.
+    pub cumulative_borrow_rate_wads: u128,
+    pub borrowed_amount_wads: u128,
+    pub market_value: u128,
+}
+
+impl ObligationLiquidity {
+    /// Applies the interest the reserve accrued since this leg was last updated.
+    pub fn accrue_interest(&mut self, cumulative_borrow_rate_wads: u128) -> Result<()> {
+        require!(
+            cumulative_borrow_rate_wads >= self.cumulative_borrow_rate_wads,
+            LiquidationError::NegativeInterestRate
+        );
+        let compounded_interest_rate = Rate::try_from(
+            Decimal::from_scaled_val(cumulative_borrow_rate_wads)
+                .try_div(Decimal::from_scaled_val(self.cumulative_borrow_rate_wads))?,
+        )?;
+        self.borrowed_amount_wads = Decimal::from_scaled_val(self.borrowed_amount_wads)
+            .try_mul(compounded_interest_rate)?
+            .to_scaled_val()?;
+        self.cumulative_borrow_rate_wads = cumulative_borrow_rate_wads;
+
+        Ok(())
+    }
+
+    /// Outstanding debt including fractions of a base unit.
+    pub fn borrowed_amount_wads(&self) -> Decimal {
+        Decimal::from_scaled_val(self.borrowed_amount_wads)
+    }
+
+    /// Outstanding debt in base units, rounded up.
+    pub fn borrowed_amount(&self) -> Result<u64> {
+        self.borrowed_amount_wads().try_ceil_u64()
+    }
+}
+
+impl Obligation {
+    /// Sets up a newly created obligation with no legs.
+    pub fn init(&mut self, lending_market: Pubkey, owner: Pubkey, index: u64, bump: u8, slot: u64) {
+        self.version = PROGRAM_VERSION;
+        self.last_update.update_slot(slot);
+        self.lending_market = lending_market;
+        self.owner = owner;
+        self.index = index;
+        self.bump = bump;
+    }
+
+    pub fn deposit(&mut self, reserve: Pubkey, amount: u64) -> Result<()> {
+        require!(
+            !self.borrows.iter().any(|leg| leg.borrow_reserve == reserve),
+            LiquidationError::DuplicateReserve
+        );
+        match self
+            .deposits
+            .iter_mut()
+            .find(|leg| leg.deposit_reserve == reserve)
+        {
+            Some(leg) => {
+                leg.deposited_amount = leg
+                    .deposited_amount
+                    .checked_add(amount)
+                    .ok_or(LiquidationError::MathOverflow)?;
+            }
+            None => {
+                require!(
+                    self.deposits.len() < MAX_OBLIGATION_LEGS,
+                    LiquidationError::ObligationLegsFull
+                );
+                self.deposits.push(ObligationCollateral {
+                    deposit_reserve: reserve,
+                    deposited_amount: amount,
+                    market_value: 0,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, reserve: Pubkey, amount: u64) -> Result<()> {
+        let index = self.find_deposit_index(reserve)?;
+        let leg = &mut self.deposits[index];
+        leg.deposited_amount = leg
+            .deposited_amount
+            .checked_sub(amount)
+            .ok_or(LiquidationError::InsufficientCollateral)?;
+        if leg.deposited_amount == 0 {
+            self.deposits.remove(index);
+        }
+
+        Ok(())
+    }
+
+    /// Adds `amount` to the borrow from `reserve`, after accruing interest on it up to
+    /// the reserve's `cumulative_borrow_rate_wads`.
+    pub fn borrow(
+        &mut self,
+        reserve: Pubkey,
+        amount: u64,
+        cumulative_borrow_rate_wads: u128,
+    ) -> Result<()> {
+        require!(
+            !self
+                .deposits
+                .iter()
+                .any(|leg| leg.deposit_reserve == reserve),
+            LiquidationError::DuplicateReserve
+        );
+        match self
+            .borrows
+            .iter_mut()
+            .find(|leg| leg.borrow_reserve == reserve)
+        {
+            Some(leg) => {
+                leg.accrue_interest(cumulative_borrow_rate_wads)?;
+                leg.borrowed_amount_wads = leg
+                    .borrowed_amount_wads()
+                    .try_add(Decimal::from(amount))?
+                    .to_scaled_val()?;
+            }
+            None => {
+                require!(
+                    self.borrows.len() < MAX_OBLIGATION_LEGS,
+                    LiquidationError::ObligationLegsFull
+                );
+                self.borrows.push(ObligationLiquidity {
+                    borrow_reserve: reserve,
+                    cumulative_borrow_rate_wads,
+                    borrowed_amount_wads: Decimal::from(amount).to_scaled_val()?,
+                    market_value: 0,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Repays up to `amount` of the borrow from `reserve`, after accruing interest on it
+    /// up to the reserve's `cumulative_borrow_rate_wads`, and returns the amount
+    /// actually repaid.
+    pub fn repay(
+        &mut self,
+        reserve: Pubkey,
+        amount: u64,
+        cumulative_borrow_rate_wads: u128,
+    ) -> Result<u64> {
+        let index = self.find_borrow_index(reserve)?;
+        let leg = &mut self.borrows[index];
+        leg.accrue_interest(cumulative_borrow_rate_wads)?;
+        let repay_amount = amount.min(leg.borrowed_amount()?);
+        // Repaying the rounded-up debt clears the fraction of a unit below it too
+        leg.borrowed_amount_wads = leg
+            .borrowed_amount_wads()
+            .saturating_sub(Decimal::from(repay_amount))
+            .to_scaled_val()?;
+        if leg.borrowed_amount_wads == 0 {
+            self.borrows.remove(index);
+        }
+
+        Ok(repay_amount)
+    }
+
+    /// Most of the borrow from `reserve` one liquidation may repay: `close_factor`
+    /// percent of it, rounded up so dust can always be cleared.
+    pub fn max_liquidation_amount(&self, reserve: Pubkey, close_factor: u8) -> Result<u64> {
+        let leg = &self.borrows[self.find_borrow_index(reserve)?];
+        Decimal::from(leg.borrowed_amount()?)
+            .try_mul(Rate::from_percent(close_factor))?
+            .try_ceil_u64()
+    }
+
+    /// Splits a liquidation repaying `amount` of the borrow from `repay_reserve` into
+    /// the amount actually repaid and the collateral seized from `withdraw_reserve`,
+    /// valued at the legs' cached market values. The seized value is the repaid value
+    /// plus `bonus` percent; when the deposit is worth less than that the whole deposit
+    /// is seized and the repay shrinks to match.
+    pub fn calculate_liquidation(
+        &self,
+        repay_reserve: Pubkey,
+        withdraw_reserve: Pubkey,
+        amount: u64,
+        bonus: u8,
+    ) -> Result<(u64, u64)> {
+        let borrow = &self.borrows[self.find_borrow_index(repay_reserve)?];
+        let deposit = &self.deposits[self.find_deposit_index(withdraw_reserve)?];
+        let borrow_value = Decimal::from_scaled_val(borrow.market_value);
+        let deposit_value = Decimal::from_scaled_val(deposit.market_value);
+        let repay_value = borrow_value
+            .try_mul(amount)?
+            .try_div(borrow.borrowed_amount_wads())?;
+        let bonus_rate = Rate::one().try_add(Rate::from_percent(bonus))?;
+        let withdraw_value = repay_value.try_mul(bonus_rate)?;
+        require!(
+            !withdraw_value.is_zero(),
+            LiquidationError::LiquidationTooSmall
+        );
+
+        // The liquidator repays rounded up and seizes rounded down
+        let (repay_amount, seize_amount) = if withdraw_value > deposit_value {
+            let repay_amount = Decimal::from(amount)
+                .try_mul(deposit_value)?
+                .try_div(withdraw_value)?
+                .try_ceil_u64()?;
+            (repay_amount, deposit.deposited_amount)
+        } else {
+            let seize_amount = Decimal::from(deposit.deposited_amount)
+                .try_mul(withdraw_value)?
+                .try_div(deposit_value)?
+                .try_floor_u64()?;
+            (amount, seize_amount)
+        };
+        require!(
+            repay_amount > 0 && seize_amount > 0,
+            LiquidationError::LiquidationTooSmall
+        );
+
+        Ok((repay_amount, seize_amount))
+    }
+
+    /// Applies a liquidation split by `calculate_liquidation` and takes the repaid and
+    /// seized value off the cached values, so the health factor reflects the liquidation
+    /// until the next refresh.
+    pub fn liquidate(
+        &mut self,
+        repay_reserve: Pubkey,
+        cumulative_borrow_rate_wads: u128,
+        repay_amount: u64,
+        withdraw_reserve: Pubkey,
+        withdraw_config: &ReserveConfig,
+        seize_amount: u64,
+    ) -> Result<()> {
+        let borrow = &self.borrows[self.find_borrow_index(repay_reserve)?];
+        let borrow_value = Decimal::from_scaled_val(borrow.market_value);
+        let repaid_value = borrow_value
+            .try_mul(repay_amount)?
+            .try_div(borrow.borrowed_amount_wads())?
+            .min(borrow_value);
+        let deposit = &self.deposits[self.find_deposit_index(withdraw_reserve)?];
+        let seized_value = Decimal::from_scaled_val(deposit.market_value)
+            .try_mul(seize_amount)?
+            .try_div(deposit.deposited_amount)?;
+
+        self.repay(repay_reserve, repay_amount, cumulative_borrow_rate_wads)?;
+        self.withdraw(withdraw_reserve, seize_amount)?;
+        let less = |value: u128, delta: Decimal| {
+            Decimal::from_scaled_val(value)
+                .saturating_sub(delta)
+                .to_scaled_val()
+        };
+        if let Ok(index) = self.find_borrow_index(repay_reserve) {
+            self.borrows[index].market_value =
+                less(self.borrows[index].market_value, repaid_value)?;
+        }
+        if let Ok(index) = self.find_deposit_index(withdraw_reserve) {
+            self.deposits[index].market_value =
+                less(self.deposits[index].market_value, seized_value)?;
+        }
+
+        self.borrowed_value = less(self.borrowed_value, repaid_value)?;
+        self.deposited_value = less(self.deposited_value, seized_value)?;
+        self.allowed_borrow_value = less(
+            self.allowed_borrow_value,
+            seized_value.try_mul(Rate::from_percent(withdraw_config.loan_to_value_ratio))?,
+        )?;
+        self.unhealthy_borrow_value = less(
+            self.unhealthy_borrow_value,
+            seized_value.try_mul(Rate::from_percent(withdraw_config.liquidation_threshold))?,
+        )?;
+        self.liquidated = true;
+
+        Ok(())
+    }
+
+    /// Drops the borrow from `reserve` once no collateral is left to back it, after
+    /// accruing interest on it up to the reserve's `cumulative_borrow_rate_wads`, and
+    /// returns the debt written off.
+    pub fn write_off_borrow(
+        &mut self,
+        reserve: Pubkey,
+        cumulative_borrow_rate_wads: u128,
+    ) -> Result<Decimal> {
+        require!(self.deposits.is_empty(), LiquidationError::NoBadDebt);
+        let index = self.find_borrow_index(reserve)?;
+        let leg = &mut self.borrows[index];
+        leg.accrue_interest(cumulative_borrow_rate_wads)?;
+        let bad_debt = leg.borrowed_amount_wads();
+        self.borrowed_value = Decimal::from_scaled_val(self.borrowed_value)
+            .saturating_sub(Decimal::from_scaled_val(leg.market_value))
+            .to_scaled_val()?;
+        self.borrows.remove(index);
+
+        Ok(bad_debt)
+    }
+
+    pub fn find_deposit_index(&self, reserve: Pubkey) -> Result<usize> {
+        self.deposits
+            .iter()
+            .position(|leg| leg.deposit_reserve == reserve)
+            .ok_or(error!(LiquidationError::ObligationLegNotFound))
+    }
+
+    pub fn find_borrow_index(&self, reserve: Pubkey) -> Result<usize> {
+        self.borrows
+            .iter()
+            .position(|leg| leg.borrow_reserve == reserve)
+            .ok_or(error!(LiquidationError::ObligationLegNotFound))
+    }
+
+    /// Applies accrued interest to every borrow leg and recomputes every leg's market
+    /// value and the cached totals from `reserves`, which must contain each reserve
+    /// the obligation references.
+    pub fn refresh_values(&mut self, reserves: &[(Pubkey, Reserve)]) -> Result<()> {
+        let find = |key: &Pubkey| {
+            reserves
+                .iter()
+                .find(|(reserve_key, _)| reserve_key == key)
+                .map(|(_, reserve)| reserve)
+                .ok_or(error!(LiquidationError::ObligationReserveMissing))
+        };
+
+        let mut deposited_value = Decimal::zero();
+        let mut allowed_borrow_value = Decimal::zero();
+        let mut unhealthy_borrow_value = Decimal::zero();
+        for leg in self.deposits.iter_mut() {
+            let reserve = find(&leg.deposit_reserve)?;
+            let market_value = reserve.market_value(Decimal::from(leg.deposited_amount))?;
+            leg.market_value = market_value.to_scaled_val()?;
+            deposited_value = deposited_value.try_add(market_value)?;
+            allowed_borrow_value = allowed_borrow_value.try_add(
+                market_value.try_mul(Rate::from_percent(reserve.config.loan_to_value_ratio))?,
+            )?;
+            unhealthy_borrow_value = unhealthy_borrow_value.try_add(
+                market_value.try_mul(Rate::from_percent(reserve.config.liquidation_threshold))?,
+            )?;
+        }
+
+        let mut borrowed_value = Decimal::zero();
+        for leg in self.borrows.iter_mut() {
+            let reserve = find(&leg.borrow_reserve)?;
+            leg.accrue_interest(reserve.liquidity.cumulative_borrow_rate_wads)?;
+            let market_value = reserve.market_value(leg.borrowed_amount_wads())?;
+            leg.market_value = market_value.to_scaled_val()?;
+            borrowed_value = borrowed_value.try_add(market_value)?;
+        }
+
+        self.deposited_value = deposited_value.to_scaled_val()?;
+        self.borrowed_value = borrowed_value.to_scaled_val()?;
+        self.allowed_borrow_value = allowed_borrow_value.to_scaled_val()?;
+        self.unhealthy_borrow_value = unhealthy_borrow_value.to_scaled_val()?;
+
+        Ok(())
+    }
+
+    /// Borrow value the deposits still support, from the cached values.
+    pub fn remaining_borrow_value(&self) -> Decimal {
+        Decimal::from_scaled_val(self.allowed_borrow_value)
+            .saturating_sub(Decimal::from_scaled_val(self.borrowed_value))
+    }
+
+    /// Most collateral that can be withdrawn from `reserve` while the remaining
+    /// deposits still support the borrows, from the cached values.
+    pub fn max_withdraw_amount(&self, deposit_reserve: Pubkey, reserve: &Reserve) -> Result<u64> {
+        let leg = &self.deposits[self.find_deposit_index(deposit_reserve)?];
+        if self.borrowed_value == 0 {
+            return Ok(leg.deposited_amount);
+        }
+
+        let max_withdraw_value = self
+            .remaining_borrow_value()
+            .try_div(Rate::from_percent(reserve.config.loan_to_value_ratio))?;
+        let deposit_value = Decimal::from_scaled_val(leg.market_value);
+        if max_withdraw_value >= deposit_value {
+            return Ok(leg.deposited_amount);
+        }
+        Decimal::from(leg.deposited_amount)
+            .try_mul(max_withdraw_value)?
+            .try_div(deposit_value)?
+            .try_floor_u64()
+    }
+
+    pub fn is_within_borrow_limit(&self) -> bool {
+        self.borrowed_value <= self.allowed_borrow_value
+    }
+
+    /// Unhealthy borrow value over borrowed value, scaled by `WAD`; below one `WAD`
+    /// the obligation can be liquidated.
+    pub fn health_factor(&self) -> Result<u128> {
+        if self.borrowed_value == 0 {
+            return Ok(u128::MAX);
+        }
+        Decimal::from_scaled_val(self.unhealthy_borrow_value)
+            .try_div(Decimal::from_scaled_val(self.borrowed_value))?
+            .to_scaled_val()
+    }
+
+    pub fn is_liquidatable(&self) -> bool {
+        self.borrowed_value > self.unhealthy_borrow_value
+    }
+}
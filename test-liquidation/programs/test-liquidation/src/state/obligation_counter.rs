@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// Hands out sequential position indexes to one owner's obligations in a lending
+/// market, so every obligation address can be derived without a keypair.
+#[account]
+#[derive(InitSpace)]
+pub struct ObligationCounter {
+    pub lending_market: Pubkey,
+    pub owner: Pubkey,
+    /// Index the owner's next obligation is derived with.
+    pub next_index: u64,
+    pub bump: u8,
+}
+
+impl ObligationCounter {
+    /// Claims the next position index.
+    pub fn claim_index(&mut self) -> u64 {
+        let index = self.next_index;
+        self.next_index += 1;
+        index
+    }
+}
@@ -0,0 +1,544 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_RESERVE_ORACLES, SLOTS_PER_YEAR};
+use crate::error::LiquidationError;
+use crate::math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub};
+use crate::state::{CircuitBreaker, LastUpdate};
+
+/// A single asset listed in a lending market, laid out after Solend's reserve.
+#[account]
+#[derive(InitSpace)]
+pub struct Reserve {
+    pub version: u8,
+    pub last_update: LastUpdate,
+    pub lending_market: Pubkey,
+    pub liquidity: ReserveLiquidity,
+    pub collateral: ReserveCollateral,
+    pub insurance: ReserveInsurance,
+    pub config: ReserveConfig,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReserveLiquidity {
+    pub mint_pubkey: Pubkey,
+    pub mint_decimals: u8,
+    /// Vault holding liquidity that can be borrowed.
+    pub supply_pubkey: Pubkey,
+    /// Price feeds in priority order; unused slots hold the default pubkey.
+    pub oracles: [ReserveOracle; MAX_RESERVE_ORACLES],
+    pub available_amount: u64,
+    /// Outstanding debt including accrued interest, scaled by `WAD`.
+    pub borrowed_amount_wads: u128,
+    /// Interest owed to the insurance fund and not yet collected into its vault,
+    /// scaled by `WAD`.
+    pub accumulated_insurance_fees_wads: u128,
+    /// Growth of one unit of debt since the reserve was created, scaled by `WAD`.
+    pub cumulative_borrow_rate_wads: u128,
+    /// Price of one whole token in USD, scaled by `WAD`.
+    pub market_price: u128,
+    /// Slot `market_price` was read from the oracles at.
+    pub market_price_slot: u64,
+    /// Price the circuit breaker measures moves from, scaled by `WAD`; zero until the
+    /// first refresh.
+    pub breaker_reference_price: u128,
+    /// Slot the circuit breaker's current window opened at.
+    pub breaker_reference_slot: u64,
+    /// Shares issued for liquidity supplied through `fund_reserve`.
+    pub supply_shares: u64,
+    /// Debt written off as bad debt since the reserve was created, whether covered
+    /// by the insurance vault or socialized, scaled by `WAD`.
+    pub total_bad_debt_wads: u128,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReserveOracle {
+    pub pubkey: Pubkey,
+    pub source: OracleSource,
+}
+
+/// Account layout an oracle is decoded with.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum OracleSource {
+    /// A feed of this workspace's `mock_oracle` program.
+    #[default]
+    Mock,
+    /// A Pyth pull-oracle `PriceUpdateV2` account, which must carry `feed_id`.
+    Pyth { feed_id: [u8; 32] },
+    /// A Switchboard V2 aggregator account.
+    Switchboard,
+}
+
+impl Reserve {
+    /// USD value of `amount` base units.
+    pub fn market_value(&self, amount: Decimal) -> Result<Decimal> {
+        amount
+            .try_mul(Decimal::from_scaled_val(self.liquidity.market_price))?
+            .try_div(10u64.pow(self.liquidity.mint_decimals as u32))
+    }
+
+    /// Annual borrow rate at the current utilization. The rate rises linearly from the
+    /// min to the optimal rate up to the optimal utilization, then steeply towards the
+    /// max rate.
+    pub fn current_borrow_rate(&self) -> Result<Rate> {
+        let config = &self.config;
+        let utilization = self.liquidity.utilization_rate()?;
+        let optimal_utilization = Rate::from_percent(config.optimal_utilization_rate);
+
+        if utilization < optimal_utilization || config.optimal_utilization_rate == 100 {
+            let normalized_rate = utilization.try_div(optimal_utilization)?;
+            let rate_range =
+                Rate::from_percent(config.optimal_borrow_rate - config.min_borrow_rate);
+            normalized_rate
+                .try_mul(rate_range)?
+                .try_add(Rate::from_percent(config.min_borrow_rate))
+        } else {
+            let normalized_rate = utilization
+                .try_sub(optimal_utilization)?
+                .try_div(Rate::one().try_sub(optimal_utilization)?)?;
+            let rate_range =
+                Rate::from_percent(config.max_borrow_rate - config.optimal_borrow_rate);
+            normalized_rate
+                .try_mul(rate_range)?
+                .try_add(Rate::from_percent(config.optimal_borrow_rate))
+        }
+    }
+
+    /// Compounds interest on outstanding debt for every slot since the last update. The
+    /// reserve is left stale, since only a refresh re-reads its price.
+    pub fn accrue_interest(&mut self, current_slot: u64) -> Result<()> {
+        let slots_elapsed = current_slot.saturating_sub(self.last_update.slot);
+        if slots_elapsed == 0 {
+            return Ok(());
+        }
+
+        let slot_rate = self.current_borrow_rate()?.try_div(SLOTS_PER_YEAR)?;
+        let compounded_interest_rate = Rate::one().try_add(slot_rate)?.try_pow(slots_elapsed)?;
+        let insurance_share = Rate::from_percent(self.config.insurance_interest_share);
+        let liquidity = &mut self.liquidity;
+        liquidity.cumulative_borrow_rate_wads =
+            Decimal::from_scaled_val(liquidity.cumulative_borrow_rate_wads)
+                .try_mul(compounded_interest_rate)?
+                .to_scaled_val()?;
+        let borrowed_amount = Decimal::from_scaled_val(liquidity.borrowed_amount_wads);
+        let new_borrowed_amount = borrowed_amount.try_mul(compounded_interest_rate)?;
+        let insurance_fees = new_borrowed_amount
+            .try_sub(borrowed_amount)?
+            .try_mul(insurance_share)?;
+        liquidity.borrowed_amount_wads = new_borrowed_amount.to_scaled_val()?;
+        liquidity.accumulated_insurance_fees_wads =
+            Decimal::from_scaled_val(liquidity.accumulated_insurance_fees_wads)
+                .try_add(insurance_fees)?
+                .to_scaled_val()?;
+        self.last_update.update_slot(current_slot);
+        self.last_update.mark_stale();
+
+        Ok(())
+    }
+}
+
+impl ReserveLiquidity {
+    /// Liquidity owed to suppliers: what is available plus what is lent out, less the
+    /// interest owed to the insurance fund.
+    pub fn total_supply(&self) -> Result<Decimal> {
+        Decimal::from(self.available_amount)
+            .try_add(Decimal::from_scaled_val(self.borrowed_amount_wads))?
+            .try_sub(Decimal::from_scaled_val(
+                self.accumulated_insurance_fees_wads,
+            ))
+    }
+
+    /// Share of the reserve's liquidity that is borrowed.
+    pub fn utilization_rate(&self) -> Result<Rate> {
+        let total_supply = self.total_supply()?;
+        if total_supply.is_zero() {
+            return Ok(Rate::zero());
+        }
+        Rate::try_from(Decimal::from_scaled_val(self.borrowed_amount_wads).try_div(total_supply)?)
+    }
+
+    /// Liquidity one supply share is worth, one before any shares are issued. Interest
+    /// and flash loan fees raise it; socialized bad debt lowers it.
+    pub fn supply_exchange_rate(&self) -> Result<Decimal> {
+        if self.supply_shares == 0 {
+            return Ok(Decimal::one());
+        }
+        self.total_supply()?.try_div(self.supply_shares)
+    }
+
+    /// Adds `amount` of supplied liquidity and returns the shares issued for it at the
+    /// current exchange rate, rounded down. Shares that socialized bad debt left worth
+    /// nothing are retired first, so the new liquidity is priced at one.
+    pub fn supply(&mut self, amount: u64) -> Result<u64> {
+        if self.total_supply()?.is_zero() {
+            self.supply_shares = 0;
+        }
+        let shares = Decimal::from(amount)
+            .try_div(self.supply_exchange_rate()?)?
+            .try_floor_u64()?;
+        self.available_amount = self
+            .available_amount
+            .checked_add(amount)
+            .ok_or(LiquidationError::MathOverflow)?;
+        self.supply_shares = self
+            .supply_shares
+            .checked_add(shares)
+            .ok_or(LiquidationError::MathOverflow)?;
+
+        Ok(shares)
+    }
+
+    /// Measures `market_price` against the circuit breaker's window, returning whether it
+    /// moved further than `breaker` allows from the price the window opened at. The
+    /// first price after a window ends opens the next one.
+    pub fn track_price_move(
+        &mut self,
+        market_price: u128,
+        slot: u64,
+        breaker: &CircuitBreaker,
+    ) -> Result<bool> {
+        if breaker.max_price_move_bps == 0 {
+            return Ok(false);
+        }
+        if self.breaker_reference_price == 0
+            || slot.saturating_sub(self.breaker_reference_slot) > breaker.window_slots
+        {
+            self.breaker_reference_price = market_price;
+            self.breaker_reference_slot = slot;
+            return Ok(false);
+        }
+
+        let price = Decimal::from_scaled_val(market_price);
+        let reference_price = Decimal::from_scaled_val(self.breaker_reference_price);
+        let price_move = price
+            .max(reference_price)
+            .try_sub(price.min(reference_price))?;
+        Ok(price_move.try_div(reference_price)? > Decimal::from_bps(breaker.max_price_move_bps))
+    }
+
+    /// The configured price feeds, primary first.
+    pub fn oracles(&self) -> &[ReserveOracle] {
+        let count = self
+            .oracles
+            .iter()
+            .take_while(|oracle| oracle.pubkey != Pubkey::default())
+            .count();
+        &self.oracles[..count]
+    }
+
+    pub fn borrow(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount <= self.available_amount,
+            LiquidationError::InsufficientLiquidity
+        );
+
+        self.available_amount = self
+            .available_amount
+            .checked_sub(amount)
+            .ok_or(LiquidationError::MathOverflow)?;
+        self.borrowed_amount_wads = Decimal::from_scaled_val(self.borrowed_amount_wads)
+            .try_add(Decimal::from(amount))?
+            .to_scaled_val()?;
+
+        Ok(())
+    }
+
+    /// Returns `amount` to the available liquidity and takes it off the debt, which
+    /// rounding may have left a fraction short of it.
+    pub fn repay(&mut self, amount: u64) -> Result<()> {
+        self.available_amount = self
+            .available_amount
+            .checked_add(amount)
+            .ok_or(LiquidationError::MathOverflow)?;
+        self.borrowed_amount_wads = Decimal::from_scaled_val(self.borrowed_amount_wads)
+            .saturating_sub(Decimal::from(amount))
+            .to_scaled_val()?;
+
+        Ok(())
+    }
+
+    /// Settles as much of the interest owed to the insurance fund as the available
+    /// liquidity allows, in whole units, and returns the amount to move into its vault.
+    pub fn collect_insurance_fees(&mut self) -> Result<u64> {
+        let amount = Decimal::from_scaled_val(self.accumulated_insurance_fees_wads)
+            .try_floor_u64()?
+            .min(self.available_amount);
+        self.available_amount = self
+            .available_amount
+            .checked_sub(amount)
+            .ok_or(LiquidationError::MathOverflow)?;
+        self.accumulated_insurance_fees_wads =
+            Decimal::from_scaled_val(self.accumulated_insurance_fees_wads)
+                .try_sub(Decimal::from(amount))?
+                .to_scaled_val()?;
+
+        Ok(amount)
+    }
+
+    /// Takes `bad_debt` off the outstanding debt. Up to `insurance_balance` of it is
+    /// covered by liquidity from the insurance vault. The rest first cancels interest
+    /// still owed to the insurance fund, which the debt will never pay, and is then
+    /// socialized, lowering the supply exchange rate. Returns the amount the insurance
+    /// vault must pay in.
+    pub fn write_off_bad_debt(&mut self, bad_debt: Decimal, insurance_balance: u64) -> Result<u64> {
+        let covered_amount = bad_debt.try_ceil_u64()?.min(insurance_balance);
+        let uncovered = bad_debt.saturating_sub(Decimal::from(covered_amount));
+        let insurance_fees = Decimal::from_scaled_val(self.accumulated_insurance_fees_wads);
+        self.accumulated_insurance_fees_wads =
+            insurance_fees.saturating_sub(uncovered).to_scaled_val()?;
+        self.available_amount = self
+            .available_amount
+            .checked_add(covered_amount)
+            .ok_or(LiquidationError::MathOverflow)?;
+        self.borrowed_amount_wads = Decimal::from_scaled_val(self.borrowed_amount_wads)
+            .saturating_sub(bad_debt)
+            .to_scaled_val()?;
+        self.total_bad_debt_wads = Decimal::from_scaled_val(self.total_bad_debt_wads)
+            .try_add(bad_debt)?
+            .to_scaled_val()?;
+
+        Ok(covered_amount)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReserveCollateral {
+    /// Vault escrowing collateral deposited by positions; never lent out.
+    pub supply_pubkey: Pubkey,
+    pub deposited_amount: u64,
+    /// Vault receiving the protocol's share of liquidation bonuses on this collateral.
+    pub treasury_pubkey: Pubkey,
+}
+
+/// The reserve's insurance vault and the shares staked into it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReserveInsurance {
+    /// Vault drawn on to cover bad debt in this reserve before suppliers bear it.
+    pub vault_pubkey: Pubkey,
+    /// Shares held by all stakers, including those waiting out an unstake cooldown, and
+    /// by the protocol.
+    pub total_shares: u64,
+    /// Shares minted to the protocol for what the vault held while no staker did, so
+    /// the next staker does not claim it. They are never unstaked.
+    pub protocol_shares: u64,
+    /// Bad debt the vault has paid for since the reserve was created.
+    pub covered_amount: u64,
+    /// Bumped when a stake finds bad debt has emptied the vault and retires the
+    /// stakers' shares; stakes from an earlier epoch hold nothing.
+    pub share_epoch: u64,
+}
+
+impl ReserveInsurance {
+    /// Issues shares for `amount` staked into a vault holding `vault_balance` and
+    /// returns how many, rounded down. Shares of an emptied vault are retired first,
+    /// so the fund can be recapitalized at one share per unit.
+    pub fn stake(&mut self, amount: u64, vault_balance: u64) -> Result<u64> {
+        if vault_balance == 0 && self.total_shares > self.protocol_shares {
+            self.total_shares = self.protocol_shares;
+            self.share_epoch = self
+                .share_epoch
+                .checked_add(1)
+                .ok_or(LiquidationError::MathOverflow)?;
+        }
+        if self.total_shares == self.protocol_shares {
+            // Without stakers the whole balance is the protocol's, one share per unit
+            self.protocol_shares = vault_balance;
+            self.total_shares = vault_balance;
+        }
+        let shares = if self.total_shares == 0 {
+            amount
+        } else {
+            require!(vault_balance > 0, LiquidationError::InsuranceVaultDepleted);
+            Decimal::from(amount)
+                .try_mul(self.total_shares)?
+                .try_div(vault_balance)?
+                .try_floor_u64()?
+        };
+        require!(shares > 0, LiquidationError::InvalidAmount);
+        self.total_shares = self
+            .total_shares
+            .checked_add(shares)
+            .ok_or(LiquidationError::MathOverflow)?;
+
+        Ok(shares)
+    }
+
+    /// Retires `shares` of a vault holding `vault_balance` and returns the amount they
+    /// redeem for, rounded down.
+    pub fn unstake(&mut self, shares: u64, vault_balance: u64) -> Result<u64> {
+        let amount = Decimal::from(vault_balance)
+            .try_mul(shares)?
+            .try_div(self.total_shares)?
+            .try_floor_u64()?;
+        self.total_shares = self
+            .total_shares
+            .checked_sub(shares)
+            .ok_or(LiquidationError::MathOverflow)?;
+
+        Ok(amount)
+    }
+}
+
+/// Risk parameters of a reserve. Ratios and rates are whole percentages unless noted.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReserveConfig {
+    /// Utilization at which the borrow rate curve bends.
+    pub optimal_utilization_rate: u8,
+    /// Maximum borrow value, as a percentage of this reserve's collateral value.
+    pub loan_to_value_ratio: u8,
+    /// Extra collateral paid to liquidators seizing this reserve's deposits, on top
+    /// of the repaid value.
+    pub liquidation_bonus: u8,
+    /// Collateral value percentage at which a position becomes liquidatable.
+    pub liquidation_threshold: u8,
+    /// Largest share of a borrow, as a percentage, one liquidation may repay.
+    pub liquidation_close_factor: u8,
+    /// Share of the liquidation bonus, as a percentage, kept by the protocol treasury.
+    pub protocol_liquidation_fee: u8,
+    /// Share of the protocol liquidation fee, as a percentage, paid into the insurance
+    /// vault instead of the treasury.
+    pub insurance_liquidation_fee_share: u8,
+    /// Share of accrued borrow interest, as a percentage, owed to the insurance fund.
+    pub insurance_interest_share: u8,
+    pub min_borrow_rate: u8,
+    pub optimal_borrow_rate: u8,
+    pub max_borrow_rate: u8,
+    /// Oldest oracle update, in slots, that prices can still be read from.
+    pub max_oracle_staleness_slots: u64,
+    /// Oldest oracle update, in seconds, that prices can still be read from.
+    pub max_oracle_staleness_seconds: u64,
+    /// Widest oracle confidence interval accepted, in basis points of the price.
+    pub max_oracle_confidence_bps: u16,
+    /// Largest move from the last accepted price, in basis points; zero disables the check.
+    pub max_price_deviation_bps: u16,
+    /// Slots the last accepted price is measured against for `max_price_deviation_bps`.
+    /// An older price is no reference, so a real move past the band only blocks
+    /// refreshes until then.
+    pub price_deviation_window_slots: u64,
+    /// Price from the oracle's exponential moving average instead of its spot price.
+    pub use_ema_price: bool,
+    /// Fee on flash loans, in basis points of the borrowed amount.
+    pub flash_loan_fee_bps: u16,
+}
+
+/// The parameters of a `ReserveConfig` that decide how much can be borrowed, when
+/// positions become liquidatable and what borrowing costs. Only executed governance
+/// proposals change them.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub struct RiskParameters {
+    pub optimal_utilization_rate: u8,
+    pub loan_to_value_ratio: u8,
+    pub liquidation_bonus: u8,
+    pub liquidation_threshold: u8,
+    pub liquidation_close_factor: u8,
+    pub min_borrow_rate: u8,
+    pub optimal_borrow_rate: u8,
+    pub max_borrow_rate: u8,
+}
+
+impl ReserveConfig {
+    pub fn risk_parameters(&self) -> RiskParameters {
+        RiskParameters {
+            optimal_utilization_rate: self.optimal_utilization_rate,
+            loan_to_value_ratio: self.loan_to_value_ratio,
+            liquidation_bonus: self.liquidation_bonus,
+            liquidation_threshold: self.liquidation_threshold,
+            liquidation_close_factor: self.liquidation_close_factor,
+            min_borrow_rate: self.min_borrow_rate,
+            optimal_borrow_rate: self.optimal_borrow_rate,
+            max_borrow_rate: self.max_borrow_rate,
+        }
+    }
+
+    pub fn set_risk_parameters(&mut self, risk_parameters: RiskParameters) {
+        self.optimal_utilization_rate = risk_parameters.optimal_utilization_rate;
+        self.loan_to_value_ratio = risk_parameters.loan_to_value_ratio;
+        self.liquidation_bonus = risk_parameters.liquidation_bonus;
+        self.liquidation_threshold = risk_parameters.liquidation_threshold;
+        self.liquidation_close_factor = risk_parameters.liquidation_close_factor;
+        self.min_borrow_rate = risk_parameters.min_borrow_rate;
+        self.optimal_borrow_rate = risk_parameters.optimal_borrow_rate;
+        self.max_borrow_rate = risk_parameters.max_borrow_rate;
+    }
+
+    /// Part of `seized_amount` collateral that is liquidation bonus rather than the
+    /// equivalent of the repaid debt.
+    pub fn liquidation_bonus_amount(&self, seized_amount: u64) -> Result<u64> {
+        let bonus_rate = Rate::one().try_add(Rate::from_percent(self.liquidation_bonus))?;
+        let repaid_equivalent = Decimal::from(seized_amount)
+            .try_div(bonus_rate)?
+            .try_floor_u64()?;
+        Ok(seized_amount - repaid_equivalent)
+    }
+
+    /// Protocol's share of the bonus contained in `seized_amount` collateral, rounded up.
+    pub fn protocol_liquidation_fee_amount(&self, seized_amount: u64) -> Result<u64> {
+        Decimal::from(self.liquidation_bonus_amount(seized_amount)?)
+            .try_mul(Rate::from_percent(self.protocol_liquidation_fee))?
+            .try_ceil_u64()
+    }
+
+    /// Part of a `protocol_fee` paid into the insurance vault, rounded down.
+    pub fn insurance_liquidation_fee_amount(&self, protocol_fee: u64) -> Result<u64> {
+        Decimal::from(protocol_fee)
+            .try_mul(Rate::from_percent(self.insurance_liquidation_fee_share))?
+            .try_floor_u64()
+    }
+
+    /// Fee owed on a flash loan of `amount`, rounded up.
+    pub fn flash_loan_fee_amount(&self, amount: u64) -> Result<u64> {
+        Decimal::from(amount)
+            .try_mul(Rate::from_bps(self.flash_loan_fee_bps))?
+            .try_ceil_u64()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.optimal_utilization_rate <= 100,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.loan_to_value_ratio > 0 && self.loan_to_value_ratio <= self.liquidation_threshold,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.liquidation_threshold <= 100,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.max_oracle_confidence_bps <= 10_000,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.flash_loan_fee_bps <= 10_000,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.max_price_deviation_bps == 0 || self.price_deviation_window_slots > 0,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.liquidation_close_factor > 0 && self.liquidation_close_factor <= 100,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.liquidation_bonus <= 100,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.protocol_liquidation_fee <= 100,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.insurance_liquidation_fee_share <= 100 && self.insurance_interest_share <= 100,
+            LiquidationError::InvalidConfig
+        );
+        require!(
+            self.min_borrow_rate <= self.optimal_borrow_rate
+                && self.optimal_borrow_rate <= self.max_borrow_rate,
+            LiquidationError::InvalidConfig
+        );
+
+        Ok(())
+    }
+}
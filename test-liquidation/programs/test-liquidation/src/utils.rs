@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::LENDING_MARKET_AUTHORITY_SEED;
+use crate::error::LiquidationError;
+use crate::state::LendingMarket;
+
+/// Moves `amount` tokens from an account owned by `authority` into a vault.
+pub fn transfer_to_vault<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    vault: &Account<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.to_account_info(),
+                to: vault.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Moves `amount` tokens out of a reserve vault, signing with the lending market authority.
+pub fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    lending_market: &Account<'info, LendingMarket>,
+    lending_market_authority: &UncheckedAccount<'info>,
+    amount: u64,
+) -> Result<()> {
+    let lending_market_key = lending_market.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        LENDING_MARKET_AUTHORITY_SEED,
+        lending_market_key.as_ref(),
+        &[lending_market.bump_seed],
+    ]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: to.to_account_info(),
+                authority: lending_market_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Index of the executing instruction in its transaction, read from the instructions
+/// sysvar. Fails unless this program was invoked directly rather than through CPI.
+pub fn current_top_level_index(instructions: &AccountInfo) -> Result<usize> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    require_keys_eq!(
+        load_instruction_at_checked(current_index, instructions)?.program_id,
+        crate::ID,
+        LiquidationError::FlashLoanCpi
+    );
+    Ok(current_index)
+}
@@ -0,0 +1,65 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import { MockOracle } from "../target/types/mock_oracle";
+
+describe("mock-oracle", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.mockOracle as Program<MockOracle>;
+
+  const initFeed = async (price: number) => {
+    const feed = Keypair.generate();
+    await program.methods
+      .initPriceFeed(new anchor.BN(price), -8, new anchor.BN(0))
+      .accountsPartial({ priceFeed: feed.publicKey })
+      .signers([feed])
+      .rpc();
+    return feed;
+  };
+
+  it("publishes prices with the current slot", async () => {
+    const feed = await initFeed(150_00000000);
+    await program.methods
+      .setPrice(new anchor.BN(90_00000000), new anchor.BN(5_000000))
+      .accountsPartial({ priceFeed: feed.publicKey })
+      .rpc();
+
+    const state = await program.account.priceFeed.fetch(feed.publicKey);
+    assert.equal(state.price.toNumber(), 90_00000000);
+    assert.equal(state.conf.toNumber(), 5_000000);
+    assert.equal(state.expo, -8);
+    assert.isAbove(state.publishSlot.toNumber(), 0);
+  });
+
+  it("updates several feeds in one instruction", async () => {
+    const feeds = [await initFeed(1_00000000), await initFeed(2_00000000)];
+    await program.methods
+      .setPriceBatch([
+        { price: new anchor.BN(3_00000000), conf: new anchor.BN(0) },
+        { price: new anchor.BN(4_00000000), conf: new anchor.BN(0) },
+      ])
+      .remainingAccounts(feeds.map((feed) => ({ pubkey: feed.publicKey, isSigner: false, isWritable: true })))
+      .rpc();
+
+    const prices = await Promise.all(feeds.map((feed) => program.account.priceFeed.fetch(feed.publicKey)));
+    assert.deepEqual(prices.map((p) => p.price.toNumber()), [3_00000000, 4_00000000]);
+  });
+
+  it("rejects updates from anyone but the authority", async () => {
+    const feed = await initFeed(1_00000000);
+    const intruder = Keypair.generate();
+    try {
+      await program.methods
+        .setPrice(new anchor.BN(1), new anchor.BN(0))
+        .accountsPartial({ priceFeed: feed.publicKey, authority: intruder.publicKey })
+        .signers([intruder])
+        .rpc();
+      assert.fail("expected Unauthorized");
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+  });
+});
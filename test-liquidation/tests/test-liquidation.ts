@@ -1,16 +1,1303 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import {
+  createAccount,
+  createMint,
+  getAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { Keypair, PublicKey, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
+import { assert } from "chai";
+import { MockOracle } from "../target/types/mock_oracle";
 import { TestLiquidation } from "../target/types/test_liquidation";
 
 describe("test-liquidation", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.testLiquidation as Program<TestLiquidation>;
+  const oracleProgram = anchor.workspace.mockOracle as Program<MockOracle>;
+  const payer = (provider.wallet as anchor.Wallet).payer;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const lendingMarket = Keypair.generate();
+  const collateralOracle = Keypair.generate();
+  const debtOracle = Keypair.generate();
+  // Second of the two governance signers; the provider wallet is the first.
+  const governor = Keypair.generate();
+  let collateralMint: PublicKey;
+  let debtMint: PublicKey;
+  let collateralReserve: PublicKey;
+  let borrowReserve: PublicKey;
+  let userCollateral: PublicKey;
+  let userDebt: PublicKey;
+  let liquidatorCollateral: PublicKey;
+  let liquidatorDebt: PublicKey;
+
+  const reserveConfig = {
+    optimalUtilizationRate: 80,
+    loanToValueRatio: 75,
+    liquidationBonus: 5,
+    liquidationThreshold: 80,
+    liquidationCloseFactor: 50,
+    protocolLiquidationFee: 20,
+    insuranceLiquidationFeeShare: 50,
+    insuranceInterestShare: 10,
+    // Interest is off so token amounts in most tests stay exact
+    minBorrowRate: 0,
+    optimalBorrowRate: 0,
+    maxBorrowRate: 0,
+    maxOracleStalenessSlots: new anchor.BN(150),
+    maxOracleStalenessSeconds: new anchor.BN(60),
+    maxOracleConfidenceBps: 200,
+    maxPriceDeviationBps: 2_500,
+    priceDeviationWindowSlots: new anchor.BN(150),
+    useEmaPrice: false,
+    flashLoanFeeBps: 30,
+  };
+
+  const riskParameters = {
+    optimalUtilizationRate: reserveConfig.optimalUtilizationRate,
+    loanToValueRatio: reserveConfig.loanToValueRatio,
+    liquidationBonus: reserveConfig.liquidationBonus,
+    liquidationThreshold: reserveConfig.liquidationThreshold,
+    liquidationCloseFactor: reserveConfig.liquidationCloseFactor,
+    minBorrowRate: reserveConfig.minBorrowRate,
+    optimalBorrowRate: reserveConfig.optimalBorrowRate,
+    maxBorrowRate: reserveConfig.maxBorrowRate,
+  };
+
+  // Pyth `PriceUpdateV2` account loaded from tests/fixtures: SOL/USD at 150 (EMA 149),
+  // published long ago, so reserves reading it accept arbitrarily old updates.
+  const PYTH_SOL_USD = new PublicKey("Cf2QhUeYe5gmhM9sFehJD1RexFPnE8JRJ2dS3QsgyDdM");
+  const SOL_USD_FEED_ID = Array.from(
+    Buffer.from("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d", "hex")
+  );
+
+  // Switchboard V2 aggregator loaded from tests/fixtures: TEST/USD at 2.5, equally old.
+  const SWITCHBOARD_TEST_USD = new PublicKey("AsJVusCZctwq2w3fTTof5eL4MKwDSMU2yS1dmux9d5Bj");
+  // The same aggregator reporting 25.5 at 18 decimals, a mantissa too large for an i64.
+  const SWITCHBOARD_TEST_USD_18_DECIMALS = new PublicKey("EKizWV9sgfQX8QX6zp1DBLoKz9mNbKvWWYNN4oTciMGe");
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const reservePda = (mint: PublicKey) =>
+    pda(Buffer.from("reserve"), lendingMarket.publicKey.toBuffer(), mint.toBuffer());
+  const liquiditySupply = (reserve: PublicKey) => pda(Buffer.from("liquidity_supply"), reserve.toBuffer());
+  const collateralSupply = (reserve: PublicKey) => pda(Buffer.from("collateral_supply"), reserve.toBuffer());
+  const treasury = (reserve: PublicKey) => pda(Buffer.from("treasury"), reserve.toBuffer());
+  const insuranceVault = (reserve: PublicKey) => pda(Buffer.from("insurance_vault"), reserve.toBuffer());
+  const insuranceFund = () => pda(Buffer.from("insurance_fund"), lendingMarket.publicKey.toBuffer());
+  const insuranceStake = (reserve: PublicKey, staker = payer.publicKey) =>
+    pda(Buffer.from("insurance_stake"), reserve.toBuffer(), staker.toBuffer());
+  const lendingMarketAuthority = () => pda(Buffer.from("authority"), lendingMarket.publicKey.toBuffer());
+  const obligationCounter = (owner = payer.publicKey) =>
+    pda(Buffer.from("obligation_counter"), lendingMarket.publicKey.toBuffer(), owner.toBuffer());
+  const obligationPda = (index: anchor.BN, owner = payer.publicKey) =>
+    pda(
+      Buffer.from("obligation"),
+      lendingMarket.publicKey.toBuffer(),
+      owner.toBuffer(),
+      index.toArrayLike(Buffer, "le", 8)
+    );
+
+  // Address the owner's next obligation will be created at.
+  const nextObligation = async (owner = payer.publicKey) => {
+    const counter = await program.account.obligationCounter.fetchNullable(obligationCounter(owner));
+    return obligationPda(counter ? counter.nextIndex : new anchor.BN(0), owner);
+  };
+
+  const WAD = new anchor.BN("1000000000000000000");
+
+  const INSURANCE_COOLDOWN_SLOTS = 4;
+  const GOVERNANCE_TIMELOCK_SLOTS = 4;
+
+  // Sleeps past a cooldown or timelock of a few slots.
+  const waitSlots = () => new Promise((resolve) => setTimeout(resolve, 3_000));
+
+  // Prices are quoted with eight decimals, so this is one USD.
+  const ONE_USD = new anchor.BN(100_000_000);
+
+  const oracleOf = (reserve: PublicKey) =>
+    reserve.equals(collateralReserve) ? collateralOracle.publicKey : debtOracle.publicKey;
+
+  const oracleMetas = (...reserves: PublicKey[]) =>
+    reserves.map((reserve) => ({ pubkey: oracleOf(reserve), isSigner: false, isWritable: false }));
+
+  const refreshReserve = (reserve: PublicKey) =>
+    program.methods
+      .refreshReserve()
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey, reserve })
+      .remainingAccounts(oracleMetas(reserve));
+
+  // Refreshes each reserve from its oracle and then the obligation, so instructions
+  // that follow in the same transaction see fresh state.
+  const refreshIxs = async (obligation: PublicKey, ...reserves: PublicKey[]) => [
+    ...(await Promise.all(
+      reserves.map((reserve) =>
+        refreshReserve(reserve).instruction()
+      )
+    )),
+    await program.methods
+      .refreshObligation()
+      .accountsPartial({ obligation })
+      .remainingAccounts(reserves.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })))
+      .instruction(),
+  ];
+
+  const setPrice = (oracle: Keypair, price: anchor.BN, conf = new anchor.BN(0)) =>
+    oracleProgram.methods.setPrice(price, conf).accountsPartial({ priceFeed: oracle.publicKey }).rpc();
+
+  const marketAccounts = () => ({
+    lendingMarket: lendingMarket.publicKey,
+    lendingMarketAuthority: lendingMarketAuthority(),
+  });
+
+  const createPositionAccounts = (obligation: PublicKey) => ({
+    ...marketAccounts(),
+    obligation,
+    collateralReserve,
+    collateralSupply: collateralSupply(collateralReserve),
+    borrowReserve,
+    liquiditySupply: liquiditySupply(borrowReserve),
+    userCollateral,
+    userDebt,
+  });
+
+  const borrowAccounts = () => ({
+    ...marketAccounts(),
+    borrowReserve,
+    liquiditySupply: liquiditySupply(borrowReserve),
+    destinationDebt: userDebt,
+  });
+
+  const withdrawAccounts = () => ({
+    ...marketAccounts(),
+    withdrawReserve: collateralReserve,
+    collateralSupply: collateralSupply(collateralReserve),
+    destinationCollateral: userCollateral,
+  });
+
+  const liquidateAccounts = () => ({
+    ...marketAccounts(),
+    repayReserve: borrowReserve,
+    liquiditySupply: liquiditySupply(borrowReserve),
+    insuranceVault: insuranceVault(borrowReserve),
+    withdrawReserve: collateralReserve,
+    collateralSupply: collateralSupply(collateralReserve),
+    treasury: treasury(collateralReserve),
+    collateralInsuranceVault: insuranceVault(collateralReserve),
+    liquidatorCollateral,
+    liquidatorDebt,
+  });
+
+  const riskProposal = (id: anchor.BN) =>
+    pda(Buffer.from("risk_proposal"), lendingMarket.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8));
+
+  const proposeRiskParameters = async (reserve: PublicKey, changes: Partial<typeof riskParameters>) => {
+    const { governance } = await program.account.lendingMarket.fetch(lendingMarket.publicKey);
+    const proposal = riskProposal(governance.nextProposalId);
+    await program.methods
+      .proposeRiskParameters({ ...riskParameters, ...changes })
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey, reserve, proposal })
+      .rpc();
+    return proposal;
+  };
+
+  const approveRiskProposal = (proposal: PublicKey, signer = governor) =>
+    program.methods
+      .approveRiskProposal()
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey, proposal, signer: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  const executeRiskProposal = (reserve: PublicKey, proposal: PublicKey) =>
+    program.methods
+      .executeRiskProposal()
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey, reserve, proposal })
+      .rpc();
+
+  // Takes a risk parameter change through both governance signers and the timelock.
+  const changeRiskParameters = async (reserve: PublicKey, changes: Partial<typeof riskParameters>) => {
+    const proposal = await proposeRiskParameters(reserve, changes);
+    await approveRiskProposal(proposal);
+    await waitSlots();
+    await executeRiskProposal(reserve, proposal);
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, code);
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  const confirmedTransaction = (signature: string) =>
+    provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+
+  // Events a confirmed transaction emitted, by name.
+  const eventsOf = async (signature: string) => {
+    const tx = await confirmedTransaction(signature);
+    return [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)];
+  };
+
+  // Reads the seized amount `liquidate` returns and the event it emits.
+  const liquidationResult = async (signature: string) => {
+    const tx = await confirmedTransaction(signature);
+    return {
+      returned: Number(Buffer.from(tx.meta.returnData.data[0], "base64").readBigUInt64LE()),
+      event: (await eventsOf(signature)).find((event) => event.name === "obligationLiquidated").data as any,
+    };
+  };
+
+  // Liquidity one supply share of a reserve is worth, scaled by `WAD`.
+  const supplyExchangeRate = async (reserve: PublicKey) => {
+    const { liquidity } = await program.account.reserve.fetch(reserve);
+    return liquidity.availableAmount
+      .mul(WAD)
+      .add(liquidity.borrowedAmountWads)
+      .sub(liquidity.accumulatedInsuranceFeesWads)
+      .div(liquidity.supplyShares);
+  };
+
+  const balance = async (tokenAccount: PublicKey) =>
+    Number((await getAccount(provider.connection, tokenAccount)).amount);
+
+  const initReserveWithOracles = async (config: any, oracleSources: any[], oracles: PublicKey[]) => {
+    const mint = await createMint(provider.connection, payer, payer.publicKey, null, 9);
+    const reserve = reservePda(mint);
+    await program.methods
+      .initReserve(config, oracleSources)
+      .accountsPartial({
+        lendingMarket: lendingMarket.publicKey,
+        lendingMarketAuthority: lendingMarketAuthority(),
+        reserve,
+        liquidityMint: mint,
+        liquiditySupply: liquiditySupply(reserve),
+        collateralSupply: collateralSupply(reserve),
+        treasury: treasury(reserve),
+        insuranceVault: insuranceVault(reserve),
+      })
+      .remainingAccounts(oracles.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })))
+      .rpc();
+    return program.account.reserve.fetch(reserve);
+  };
+
+  before(async () => {
+    collateralMint = await createMint(provider.connection, payer, payer.publicKey, null, 9);
+    debtMint = await createMint(provider.connection, payer, payer.publicKey, null, 9);
+
+    userCollateral = await createAccount(provider.connection, payer, collateralMint, payer.publicKey, Keypair.generate());
+    userDebt = await createAccount(provider.connection, payer, debtMint, payer.publicKey, Keypair.generate());
+    liquidatorCollateral = await createAccount(provider.connection, payer, collateralMint, payer.publicKey, Keypair.generate());
+    liquidatorDebt = await createAccount(provider.connection, payer, debtMint, payer.publicKey, Keypair.generate());
+
+    await mintTo(provider.connection, payer, collateralMint, userCollateral, payer, 1_000_000_000);
+    await mintTo(provider.connection, payer, debtMint, liquidatorDebt, payer, 1_000_000_000);
+
+    await program.methods
+      .initLendingMarket()
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey })
+      .signers([lendingMarket])
+      .rpc();
+    await program.methods
+      .initInsuranceFund(new anchor.BN(INSURANCE_COOLDOWN_SLOTS))
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey, insuranceFund: insuranceFund() })
+      .rpc();
+    await program.methods
+      .initGovernance([payer.publicKey, governor.publicKey], 2, new anchor.BN(GOVERNANCE_TIMELOCK_SLOTS))
+      .accountsPartial({ lendingMarket: lendingMarket.publicKey })
+      .rpc();
+
+    for (const oracle of [collateralOracle, debtOracle]) {
+      await oracleProgram.methods
+        .initPriceFeed(ONE_USD, -8, new anchor.BN(0))
+        .accountsPartial({ priceFeed: oracle.publicKey })
+        .signers([oracle])
+        .rpc();
+    }
+
+    collateralReserve = reservePda(collateralMint);
+    borrowReserve = reservePda(debtMint);
+    for (const [mint, reserve] of [[collateralMint, collateralReserve], [debtMint, borrowReserve]]) {
+      await program.methods
+        .initReserve(reserveConfig, [{ mock: {} }])
+        .accountsPartial({
+          lendingMarket: lendingMarket.publicKey,
+          lendingMarketAuthority: lendingMarketAuthority(),
+          reserve,
+          liquidityMint: mint,
+          liquiditySupply: liquiditySupply(reserve),
+          collateralSupply: collateralSupply(reserve),
+          treasury: treasury(reserve),
+          insuranceVault: insuranceVault(reserve),
+        })
+        .remainingAccounts(oracleMetas(reserve))
+        .rpc();
+    }
+
+    const funderDebt = await createAccount(provider.connection, payer, debtMint, payer.publicKey, Keypair.generate());
+    await mintTo(provider.connection, payer, debtMint, funderDebt, payer, 1_000_000_000);
+    await program.methods
+      .fundReserve(new anchor.BN(1_000_000_000))
+      .accountsPartial({
+        lendingMarket: lendingMarket.publicKey,
+        reserve: borrowReserve,
+        liquiditySupply: liquiditySupply(borrowReserve),
+        funderLiquidity: funderDebt,
+      })
+      .rpc();
+  });
+
+  it("only lets the market owner open risky positions", async () => {
+    const stranger = Keypair.generate();
+    await expectError(
+      program.methods
+        .createRiskyPosition()
+        .accountsPartial({ position: createPositionAccounts(await nextObligation()), owner: stranger.publicKey })
+        .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("escrows collateral and liquidates with real token transfers", async () => {
+    const collateralSupplyBefore = await balance(collateralSupply(collateralReserve));
+    const obligation = await nextObligation();
+    await program.methods
+      .createRiskyPosition()
+      .accountsPartial({ position: createPositionAccounts(obligation), owner: payer.publicKey })
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+
+    assert.equal(await balance(collateralSupply(collateralReserve)) - collateralSupplyBefore, 100_000_000);
+    assert.equal(await balance(userDebt), 200_000_000);
+
+    // Half the debt plus the 5% bonus is worth more than all of the collateral, so
+    // the whole deposit is seized and the repay shrinks to 0.1 / 1.05
+    const signature = await program.methods
+      .liquidate(new anchor.BN(100_000_000))
+      .accountsPartial({ ...liquidateAccounts(), obligation })
+      .preInstructions(await refreshIxs(obligation, collateralReserve, borrowReserve))
+      .rpc({ commitment: "confirmed" });
+
+    // Nothing is left to back the rest of the debt, so it is written off
+    const state = await program.account.obligation.fetch(obligation);
+    assert.equal(state.deposits.length, 0);
+    assert.equal(state.borrows.length, 0);
+    // 20% of the 4_761_905 bonus is kept by the protocol, half of it for insurance
+    assert.equal(await balance(liquidatorCollateral), 99_047_619);
+    assert.equal(await balance(treasury(collateralReserve)), 476_191);
+    assert.equal(await balance(insuranceVault(collateralReserve)), 476_190);
+    assert.equal(await balance(liquidatorDebt), 904_761_904);
+
+    const { returned, event } = await liquidationResult(signature);
+    assert.equal(returned, 100_000_000);
+    assert.equal(event.seizedAmount.toNumber(), 100_000_000);
+    assert.equal(event.repaidAmount.toNumber(), 95_238_096);
+    assert.equal(event.bonusAmount.toNumber(), 4_761_905);
+    assert.equal(event.protocolFee.toNumber(), 952_381);
+    assert.equal(event.insuranceFee.toNumber(), 476_190);
+    // Every deposit was seized, leaving nothing to back the remaining borrow
+    assert.ok(event.healthFactorBefore.lt(WAD));
+    assert.ok(event.healthFactorAfter.isZero());
+
+    const badDebt = (await eventsOf(signature)).find((event) => event.name === "badDebtWrittenOff").data as any;
+    assert.equal(badDebt.badDebt.toNumber(), 104_761_904);
+    assert.equal(badDebt.insuranceCovered.toNumber(), 0);
+    assert.equal(badDebt.socialized.toNumber(), 104_761_904);
+    // Suppliers of the 1_000_000_000 funded units bear the socialized debt
+    assert.ok(badDebt.supplyExchangeRate.eq(WAD.muln(895_238_096).divn(1_000_000_000)));
+    assert.ok(badDebt.supplyExchangeRate.eq(await supplyExchangeRate(borrowReserve)));
+  });
+
+  it("covers bad debt from the insurance vault before socializing the rest", async () => {
+    await mintTo(provider.connection, payer, debtMint, insuranceVault(borrowReserve), payer, 60_000_000);
+    const rateBefore = await supplyExchangeRate(borrowReserve);
+    const feesBefore = (await program.account.reserve.fetch(borrowReserve)).liquidity.accumulatedInsuranceFeesWads;
+    const obligation = await nextObligation();
+    await program.methods
+      .createRiskyPosition()
+      .accountsPartial({ position: createPositionAccounts(obligation), owner: payer.publicKey })
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+
+    const signature = await program.methods
+      .liquidate(new anchor.BN(100_000_000))
+      .accountsPartial({ ...liquidateAccounts(), obligation })
+      .preInstructions(await refreshIxs(obligation, collateralReserve, borrowReserve))
+      .rpc({ commitment: "confirmed" });
+
+    const badDebt = (await eventsOf(signature)).find((event) => event.name === "badDebtWrittenOff").data as any;
+    assert.equal(badDebt.badDebt.toNumber(), 104_761_904);
+    assert.equal(badDebt.insuranceCovered.toNumber(), 60_000_000);
+    assert.equal(badDebt.socialized.toNumber(), 44_761_904);
+    assert.equal(await balance(insuranceVault(borrowReserve)), 0);
+    const reserve = await program.account.reserve.fetch(borrowReserve);
+    assert.equal(reserve.liquidity.availableAmount.toNumber(), await balance(liquiditySupply(borrowReserve)));
+    assert.equal(reserve.insurance.coveredAmount.toNumber(), 60_000_000);
+    // Interest the insurance fund was owed on the lost debt is cancelled with it
+    assert.ok(reserve.liquidity.accumulatedInsuranceFeesWads.isZero());
+    assert.ok(
+      rateBefore.sub(badDebt.supplyExchangeRate).eq(WAD.muln(44_761_904).sub(feesBefore).divn(1_000_000_000))
+    );
+
+    // The write-off crank leaves debt that is still backed by collateral alone
+    const healthy = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(10_000_000))
+      .accountsPartial(createPositionAccounts(healthy))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+    await expectError(
+      program.methods
+        .writeOffBadDebt()
+        .accountsPartial({
+          ...marketAccounts(),
+          obligation: healthy,
+          reserve: borrowReserve,
+          liquiditySupply: liquiditySupply(borrowReserve),
+          insuranceVault: insuranceVault(borrowReserve),
+        })
+        .rpc(),
+      "NoBadDebt"
+    );
+  });
+
+  it("liquidates only once oracle prices breach the liquidation threshold", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(70_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+
+    const refresh = await refreshIxs(obligation, collateralReserve, borrowReserve);
+    const liquidate = (repayAmount: number) =>
+      program.methods
+        .liquidate(new anchor.BN(repayAmount))
+        .accountsPartial({ ...liquidateAccounts(), obligation })
+        .preInstructions(refresh)
+        .rpc();
+
+    // 70 borrowed against 80% of 100 collateral at one USD each
+    await expectError(liquidate(35_000_000), "NotLiquidatable");
+
+    // At 0.80 USD the collateral only supports 64 USD of debt; a 50% close
+    // factor caps the repay at 35, which seizes 36.75 USD of collateral
+    await setPrice(collateralOracle, ONE_USD.muln(80).divn(100));
+    try {
+      await expectError(liquidate(35_000_001), "LiquidationTooLarge");
+      await liquidate(35_000_000);
+    } finally {
+      await setPrice(collateralOracle, ONE_USD);
+    }
+
+    const state = await program.account.obligation.fetch(obligation);
+    assert.equal(state.borrows[0].borrowedAmountWads.div(WAD).toNumber(), 35_000_000);
+    assert.equal(state.deposits[0].depositedAmount.toNumber(), 54_062_500);
+  });
+
+  it("opens obligations within the allowed borrow value", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(50_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+
+    const state = await program.account.obligation.fetch(obligation);
+    assert.equal(state.deposits[0].depositedAmount.toNumber(), 100_000_000);
+    assert.equal(state.borrows[0].borrowedAmountWads.div(WAD).toNumber(), 50_000_000);
+    // Both reserves price one whole token at one USD, so 0.1 tokens are worth 0.1 USD
+    assert.ok(state.depositedValue.eq(WAD.divn(10)));
+    assert.ok(state.allowedBorrowValue.eq(WAD.muln(75).divn(1000)));
+    assert.ok(state.unhealthyBorrowValue.eq(WAD.muln(80).divn(1000)));
+    assert.ok(state.borrowedValue.eq(WAD.divn(20)));
+
+    // 50 + 30 > 75% of 100
+    const refresh = await refreshIxs(obligation, collateralReserve, borrowReserve);
+    await expectError(
+      program.methods
+        .borrow(new anchor.BN(30_000_000))
+        .accountsPartial({ ...borrowAccounts(), obligation })
+        .preInstructions(refresh)
+        .rpc(),
+      "BorrowTooLarge"
+    );
+    await expectError(
+      program.methods
+        .withdraw(new anchor.BN(40_000_000))
+        .accountsPartial({ ...withdrawAccounts(), obligation })
+        .preInstructions(refresh)
+        .rpc(),
+      "WithdrawTooLarge"
+    );
+
+    await program.methods
+      .repay(new anchor.BN(50_000_000))
+      .accountsPartial({
+        obligation,
+        lendingMarket: lendingMarket.publicKey,
+        repayReserve: borrowReserve,
+        liquiditySupply: liquiditySupply(borrowReserve),
+        repayerDebt: userDebt,
+      })
+      .rpc();
+    await program.methods
+      .withdraw(new anchor.BN(100_000_000))
+      .accountsPartial({ ...withdrawAccounts(), obligation })
+      .preInstructions(await refreshIxs(obligation, collateralReserve))
+      .rpc();
+    await program.methods
+      .refreshObligation()
+      .accountsPartial({ obligation })
+      .rpc();
+
+    const closed = await program.account.obligation.fetch(obligation);
+    assert.equal(closed.deposits.length, 0);
+    assert.equal(closed.borrows.length, 0);
+    assert.ok(closed.depositedValue.isZero());
+  });
+
+  it("adds deposit legs to an existing obligation", async () => {
+    const obligation = await nextObligation();
+    const opened = await program.methods
+      .initObligation()
+      .accountsPartial({ obligation, lendingMarket: lendingMarket.publicKey })
+      .rpc({ commitment: "confirmed" });
+
+    const deposited = await program.methods
+      .deposit(new anchor.BN(40_000_000))
+      .accountsPartial({
+        obligation,
+        lendingMarket: lendingMarket.publicKey,
+        depositReserve: collateralReserve,
+        collateralSupply: collateralSupply(collateralReserve),
+        ownerCollateral: userCollateral,
+      })
+      .rpc({ commitment: "confirmed" });
+    const borrowed = await program.methods
+      .borrow(new anchor.BN(30_000_000))
+      .accountsPartial({ ...borrowAccounts(), obligation })
+      .preInstructions(await refreshIxs(obligation, collateralReserve, borrowReserve))
+      .rpc({ commitment: "confirmed" });
+
+    const state = await program.account.obligation.fetch(obligation);
+    assert.equal(state.deposits.length, 1);
+    assert.equal(state.borrows.length, 1);
+    assert.ok(obligationPda(state.index).equals(obligation));
+    const counter = await program.account.obligationCounter.fetch(obligationCounter());
+    assert.ok(counter.nextIndex.eq(state.index.addn(1)));
+    assert.ok(state.borrows[0].borrowReserve.equals(borrowReserve));
+
+    const [openedEvent] = await eventsOf(opened);
+    assert.equal(openedEvent.name, "positionOpened");
+    assert.ok(openedEvent.data.index.eq(state.index));
+    const [depositedEvent] = await eventsOf(deposited);
+    assert.equal(depositedEvent.name, "collateralDeposited");
+    assert.equal(depositedEvent.data.amount.toNumber(), 40_000_000);
+    const borrowedEvent = (await eventsOf(borrowed)).find((event) => event.name === "liquidityBorrowed");
+    assert.equal(borrowedEvent.data.amount.toNumber(), 30_000_000);
+  });
+
+  it("closes a position, repaying its debt and returning its collateral", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(50_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+    const collateralBefore = await balance(userCollateral);
+    const debtBefore = await balance(userDebt);
+
+    const writable = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+    await program.methods
+      .closePosition()
+      .accountsPartial({ ...marketAccounts(), obligation })
+      .remainingAccounts([
+        writable(borrowReserve),
+        writable(liquiditySupply(borrowReserve)),
+        writable(userDebt),
+        writable(collateralReserve),
+        writable(collateralSupply(collateralReserve)),
+        writable(userCollateral),
+      ])
+      .rpc();
+
+    assert.equal(await balance(userCollateral) - collateralBefore, 100_000_000);
+    assert.equal(debtBefore - await balance(userDebt), 50_000_000);
+    assert.isNull(await program.account.obligation.fetchNullable(obligation));
+  });
+
+  it("only lets anyone close positions emptied by liquidation", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .initObligation()
+      .accountsPartial({ obligation, lendingMarket: lendingMarket.publicKey })
+      .rpc();
+
+    await expectError(
+      program.methods.closeEmptyPosition().accountsPartial({ obligation, owner: payer.publicKey }).rpc(),
+      "ObligationNotEmpty"
+    );
+  });
+
+  it("rejects borrows, withdrawals and deposits signed by anyone but the owner", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(10_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+
+    const stranger = Keypair.generate();
+    const refresh = await refreshIxs(obligation, collateralReserve, borrowReserve);
+    await expectError(
+      program.methods
+        .borrow(new anchor.BN(1_000_000))
+        .accountsPartial({ ...borrowAccounts(), obligation, owner: stranger.publicKey })
+        .preInstructions(refresh)
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+    await expectError(
+      program.methods
+        .withdraw(new anchor.BN(1_000_000))
+        .accountsPartial({ ...withdrawAccounts(), obligation, owner: stranger.publicKey })
+        .preInstructions(refresh)
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+    await expectError(
+      program.methods
+        .deposit(new anchor.BN(1_000_000))
+        .accountsPartial({
+          obligation,
+          lendingMarket: lendingMarket.publicKey,
+          depositReserve: collateralReserve,
+          collateralSupply: collateralSupply(collateralReserve),
+          ownerCollateral: userCollateral,
+          owner: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("tracks liquidity and collateral on each reserve", async () => {
+    const collateral = await program.account.reserve.fetch(collateralReserve);
+    const borrow = await program.account.reserve.fetch(borrowReserve);
+    assert.ok(collateral.liquidity.mintPubkey.equals(collateralMint));
+    assert.ok(collateral.lendingMarket.equals(lendingMarket.publicKey));
+    assert.equal(collateral.config.liquidationThreshold, 80);
+    assert.equal(collateral.collateral.depositedAmount.toNumber(), await balance(collateralSupply(collateralReserve)));
+    assert.equal(borrow.liquidity.availableAmount.toNumber(), await balance(liquiditySupply(borrowReserve)));
+  });
+
+  it("rejects obligations that open above the allowed borrow value", async () => {
+    const obligation = await nextObligation();
+    await expectError(
+      program.methods
+        .createPosition(new anchor.BN(100_000_000), new anchor.BN(80_000_000))
+        .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+        .rpc(),
+      "BorrowTooLarge"
+    );
+  });
+
+  it("rejects oracle prices with wide confidence or large moves", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(50_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+
+    const refresh = await refreshIxs(obligation, collateralReserve, borrowReserve);
+    const liquidate = () =>
+      program.methods
+        .liquidate(new anchor.BN(1_000_000))
+        .accountsPartial({ ...liquidateAccounts(), obligation })
+        .preInstructions(refresh)
+        .rpc();
+
+    try {
+      // A 5% confidence interval is wider than the configured 2%
+      await setPrice(collateralOracle, ONE_USD, ONE_USD.divn(20));
+      await expectError(liquidate(), "OracleConfidenceTooWide");
+
+      // Halving the price exceeds the 25% deviation band
+      await setPrice(collateralOracle, ONE_USD.divn(2));
+      await expectError(liquidate(), "OraclePriceDeviation");
+
+      // Once the last accepted price is older than the window, a move that persists is accepted
+      const setDeviationWindow = (slots: number) =>
+        program.methods
+          .updateReserveConfig({ ...reserveConfig, priceDeviationWindowSlots: new anchor.BN(slots) })
+          .accountsPartial({ lendingMarket: lendingMarket.publicKey, reserve: collateralReserve })
+          .rpc();
+      await setDeviationWindow(1);
+      try {
+        await waitSlots();
+        await refreshReserve(collateralReserve).rpc();
+        const reserve = await program.account.reserve.fetch(collateralReserve);
+        assert.ok(reserve.liquidity.marketPrice.eq(WAD.divn(2)));
+
+        await setPrice(collateralOracle, ONE_USD);
+        await waitSlots();
+        await refreshReserve(collateralReserve).rpc();
+      } finally {
+        await setDeviationWindow(150);
+      }
+    } finally {
+      await setPrice(collateralOracle, ONE_USD);
+    }
+  });
+
+  it("prices reserves from Pyth and Switchboard accounts", async () => {
+    const staleConfig = {
+      ...reserveConfig,
+      maxOracleStalenessSlots: new anchor.BN("18446744073709551615"),
+      maxOracleStalenessSeconds: new anchor.BN("18446744073709551615"),
+    };
+    const initReserveWith = (config: typeof staleConfig, oracleSource: any, oracle: PublicKey) =>
+      initReserveWithOracles(config, [oracleSource], [oracle]);
+    const pyth = { pyth: { feedId: SOL_USD_FEED_ID } };
+
+    const spot = await initReserveWith(staleConfig, pyth, PYTH_SOL_USD);
+    assert.ok(spot.liquidity.marketPrice.eq(WAD.muln(150)));
+
+    const ema = await initReserveWith({ ...staleConfig, useEmaPrice: true }, pyth, PYTH_SOL_USD);
+    assert.ok(ema.liquidity.marketPrice.eq(WAD.muln(149)));
+
+    const switchboard = await initReserveWith(staleConfig, { switchboard: {} }, SWITCHBOARD_TEST_USD);
+    assert.ok(switchboard.liquidity.marketPrice.eq(WAD.muln(5).divn(2)));
+    const precise = await initReserveWith(staleConfig, { switchboard: {} }, SWITCHBOARD_TEST_USD_18_DECIMALS);
+    assert.ok(precise.liquidity.marketPrice.eq(WAD.muln(51).divn(2)));
+
+    await expectError(
+      initReserveWith(staleConfig, { pyth: { feedId: new Array(32).fill(0) } }, PYTH_SOL_USD),
+      "InvalidOracleFeedId"
+    );
+    await expectError(initReserveWith(reserveConfig, pyth, PYTH_SOL_USD), "StaleOracle");
+    await expectError(initReserveWith(staleConfig, pyth, SWITCHBOARD_TEST_USD), "InvalidOracle");
+    await expectError(initReserveWith(staleConfig, { switchboard: {} }, collateralOracle.publicKey), "InvalidOracle");
+  });
+
+  it("takes the median of three oracles and falls back past unhealthy ones", async () => {
+    const feeds = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const prices = [100, 110, 95].map((cents) => ONE_USD.muln(cents).divn(100));
+    for (const [i, feed] of feeds.entries()) {
+      await oracleProgram.methods
+        .initPriceFeed(prices[i], -8, new anchor.BN(0))
+        .accountsPartial({ priceFeed: feed.publicKey })
+        .signers([feed])
+        .rpc();
+    }
+    const mocks = feeds.map(() => ({ mock: {} }));
+    const keys = feeds.map((feed) => feed.publicKey);
+
+    const median = await initReserveWithOracles(reserveConfig, mocks, keys);
+    assert.ok(median.liquidity.marketPrice.eq(WAD));
+
+    // A primary with a 5% confidence interval is skipped for the secondary
+    await setPrice(feeds[0], prices[0], ONE_USD.divn(20));
+    const secondary = await initReserveWithOracles(reserveConfig, mocks, keys);
+    assert.ok(secondary.liquidity.marketPrice.eq(WAD.muln(110).divn(100)));
+
+    await setPrice(feeds[1], prices[1], ONE_USD.divn(20));
+    const tertiary = await initReserveWithOracles(reserveConfig, mocks, keys);
+    assert.ok(tertiary.liquidity.marketPrice.eq(WAD.muln(95).divn(100)));
+
+    await setPrice(feeds[2], prices[2], ONE_USD.divn(20));
+    await expectError(initReserveWithOracles(reserveConfig, mocks, keys), "OracleConfidenceTooWide");
+    await expectError(initReserveWithOracles(reserveConfig, mocks, keys.slice(0, 2)), "InvalidOracle");
+  });
+
+  it("lends flash loans that are repaid with a fee in the same transaction", async () => {
+    const flashBorrow = (amount: number) =>
+      program.methods.flashBorrow(new anchor.BN(amount)).accountsPartial({
+        ...marketAccounts(),
+        reserve: borrowReserve,
+        liquiditySupply: liquiditySupply(borrowReserve),
+        destinationLiquidity: userDebt,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      });
+    const flashRepay = (amount: number, borrowInstructionIndex = 0) =>
+      program.methods
+        .flashRepay(new anchor.BN(amount), borrowInstructionIndex)
+        .accountsPartial({
+          lendingMarket: lendingMarket.publicKey,
+          reserve: borrowReserve,
+          liquiditySupply: liquiditySupply(borrowReserve),
+          sourceLiquidity: userDebt,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+    const availableBefore = (await program.account.reserve.fetch(borrowReserve)).liquidity.availableAmount;
+    const debtBefore = await balance(userDebt);
+    await flashBorrow(10_000_000).postInstructions([await flashRepay(10_000_000)]).rpc();
+
+    // 30 bps of the loan stays with the reserve
+    const availableAfter = (await program.account.reserve.fetch(borrowReserve)).liquidity.availableAmount;
+    assert.equal(availableAfter.sub(availableBefore).toNumber(), 30_000);
+    assert.equal(debtBefore - await balance(userDebt), 30_000);
+
+    await expectError(flashBorrow(10_000_000).rpc(), "FlashRepayMissing");
+    await expectError(
+      flashBorrow(10_000_000).postInstructions([await flashRepay(9_000_000)]).rpc(),
+      "InvalidFlashRepay"
+    );
+    await expectError(
+      flashBorrow(10_000_000)
+        .postInstructions([await flashBorrow(1_000_000).instruction(), await flashRepay(10_000_000)])
+        .rpc(),
+      "MultipleFlashBorrows"
+    );
+
+    // Interest is free below the optimal utilization and steep above it, where a
+    // refresh during the loan would otherwise charge every slot since the last one
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(10_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+    await changeRiskParameters(borrowReserve, { maxBorrowRate: 200 });
+    try {
+      await waitSlots();
+      const { liquidity } = await program.account.reserve.fetch(borrowReserve);
+      const amount = liquidity.availableAmount.toNumber() - 1;
+      await flashBorrow(amount)
+        .postInstructions([await refreshReserve(borrowReserve).instruction(), await flashRepay(amount)])
+        .rpc();
+      const after = (await program.account.reserve.fetch(borrowReserve)).liquidity;
+      assert.ok(after.cumulativeBorrowRateWads.eq(liquidity.cumulativeBorrowRateWads));
+    } finally {
+      await changeRiskParameters(borrowReserve, { maxBorrowRate: 0 });
+    }
+  });
+
+  it("stakes into the insurance fund and unstakes after a cooldown", async () => {
+    const vault = insuranceVault(borrowReserve);
+    const stake = insuranceStake(borrowReserve);
+    const { totalShares } = (await program.account.reserve.fetch(borrowReserve)).insurance;
+    const vaultBalance = await balance(vault);
+    await program.methods
+      .stakeInsurance(new anchor.BN(10_000_000))
+      .accountsPartial({
+        lendingMarket: lendingMarket.publicKey,
+        insuranceFund: insuranceFund(),
+        reserve: borrowReserve,
+        insuranceVault: vault,
+        insuranceStake: stake,
+        stakerLiquidity: liquidatorDebt,
+      })
+      .rpc();
+    // Bad debt emptied the vault and its shares before, so shares start at one per unit
+    assert.equal(vaultBalance, 0);
+    assert.ok(totalShares.isZero());
+    assert.equal((await program.account.insuranceStake.fetch(stake)).shares.toNumber(), 10_000_000);
+
+    const requestUnstake = (shares: number) =>
+      program.methods.requestInsuranceUnstake(new anchor.BN(shares)).accountsPartial({ insuranceStake: stake });
+    const unstake = () =>
+      program.methods.unstakeInsurance().accountsPartial({
+        ...marketAccounts(),
+        insuranceFund: insuranceFund(),
+        reserve: borrowReserve,
+        insuranceVault: vault,
+        insuranceStake: stake,
+        destinationLiquidity: liquidatorDebt,
+      });
+
+    await expectError(requestUnstake(10_000_001).rpc(), "InsufficientInsuranceShares");
+    const stranger = Keypair.generate();
+    await expectError(
+      requestUnstake(1).accountsPartial({ staker: stranger.publicKey }).signers([stranger]).rpc(),
+      "Unauthorized"
+    );
+    await requestUnstake(4_000_000).rpc();
+    await expectError(unstake().rpc(), "InsuranceCooldownActive");
+
+    await waitSlots();
+    const debtBefore = await balance(liquidatorDebt);
+    await unstake().rpc();
+    assert.equal(await balance(liquidatorDebt) - debtBefore, 4_000_000);
+    const state = await program.account.insuranceStake.fetch(stake);
+    assert.equal(state.shares.toNumber(), 6_000_000);
+    assert.ok(state.unstakingShares.isZero());
+    assert.equal((await program.account.reserve.fetch(borrowReserve)).insurance.totalShares.toNumber(), 6_000_000);
+  });
+
+  it("recapitalizes the insurance fund once bad debt has emptied its vault", async () => {
+    const vault = insuranceVault(borrowReserve);
+    const stake = insuranceStake(borrowReserve);
+    assert.ok((await program.account.insuranceStake.fetch(stake)).shares.gtn(0));
+    const obligation = await nextObligation();
+    await program.methods
+      .createRiskyPosition()
+      .accountsPartial({ position: createPositionAccounts(obligation), owner: payer.publicKey })
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+    await program.methods
+      .liquidate(new anchor.BN(100_000_000))
+      .accountsPartial({ ...liquidateAccounts(), obligation })
+      .preInstructions(await refreshIxs(obligation, collateralReserve, borrowReserve))
+      .rpc();
+    assert.equal(await balance(vault), 0);
+    const { shareEpoch } = (await program.account.reserve.fetch(borrowReserve)).insurance;
+
+    await program.methods
+      .stakeInsurance(new anchor.BN(5_000_000))
+      .accountsPartial({
+        lendingMarket: lendingMarket.publicKey,
+        insuranceFund: insuranceFund(),
+        reserve: borrowReserve,
+        insuranceVault: vault,
+        insuranceStake: stake,
+        stakerLiquidity: liquidatorDebt,
+      })
+      .rpc();
+
+    // Shares from before the write-off are retired, so new ones start at one per unit
+    const { insurance } = await program.account.reserve.fetch(borrowReserve);
+    assert.ok(insurance.shareEpoch.eq(shareEpoch.addn(1)));
+    assert.equal(insurance.totalShares.toNumber(), 5_000_000);
+    const state = await program.account.insuranceStake.fetch(stake);
+    assert.equal(state.shares.toNumber(), 5_000_000);
+    assert.ok(state.shareEpoch.eq(insurance.shareEpoch));
+  });
+
+  it("keeps what the insurance vault held before the first stake for the protocol", async () => {
+    const vault = insuranceVault(collateralReserve);
+    const stake = insuranceStake(collateralReserve);
+    await mintTo(provider.connection, payer, collateralMint, vault, payer, 5_000_000);
+    const vaultBalance = await balance(vault);
+    assert.ok((await program.account.reserve.fetch(collateralReserve)).insurance.totalShares.isZero());
+
+    await program.methods
+      .stakeInsurance(new anchor.BN(10_000_000))
+      .accountsPartial({
+        lendingMarket: lendingMarket.publicKey,
+        insuranceFund: insuranceFund(),
+        reserve: collateralReserve,
+        insuranceVault: vault,
+        insuranceStake: stake,
+        stakerLiquidity: liquidatorCollateral,
+      })
+      .rpc();
+
+    // The staker's shares are worth what they paid in, not a claim on the earlier balance
+    const { insurance } = await program.account.reserve.fetch(collateralReserve);
+    assert.equal(insurance.protocolShares.toNumber(), vaultBalance);
+    assert.equal(insurance.totalShares.toNumber(), vaultBalance + 10_000_000);
+    assert.equal((await program.account.insuranceStake.fetch(stake)).shares.toNumber(), 10_000_000);
+  });
+
+  it("lets the market owner withdraw protocol fees", async () => {
+    const fees = await balance(treasury(collateralReserve));
+    assert.isAbove(fees, 0);
+    const destination = await createAccount(provider.connection, payer, collateralMint, payer.publicKey, Keypair.generate());
+
+    const withdrawFees = (amount: number) =>
+      program.methods
+        .withdrawProtocolFees(new anchor.BN(amount))
+        .accountsPartial({
+          ...marketAccounts(),
+          reserve: collateralReserve,
+          treasury: treasury(collateralReserve),
+          destination,
+        })
+        .rpc();
+
+    const stranger = Keypair.generate();
+    await expectError(
+      program.methods
+        .withdrawProtocolFees(new anchor.BN(fees))
+        .accountsPartial({
+          ...marketAccounts(),
+          reserve: collateralReserve,
+          treasury: treasury(collateralReserve),
+          destination,
+          owner: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+    await expectError(withdrawFees(fees + 1), "InsufficientLiquidity");
+    await withdrawFees(fees);
+    assert.equal(await balance(destination), fees);
+    assert.equal(await balance(treasury(collateralReserve)), 0);
+  });
+
+  it("accrues interest on borrows as slots pass", async () => {
+    const setBorrowRates = (minBorrowRate: number, optimalBorrowRate: number, maxBorrowRate: number) =>
+      changeRiskParameters(borrowReserve, { minBorrowRate, optimalBorrowRate, maxBorrowRate });
+
+    const obligation = await nextObligation();
+    await setBorrowRates(100, 150, 200);
+    try {
+      await program.methods
+        .createPosition(new anchor.BN(100_000_000), new anchor.BN(50_000_000))
+        .accountsPartial(createPositionAccounts(obligation))
+        .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+        .rpc();
+      const indexBefore = (await program.account.reserve.fetch(borrowReserve)).liquidity.cumulativeBorrowRateWads;
+
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+      const tx = new anchor.web3.Transaction().add(
+        ...(await refreshIxs(obligation, collateralReserve, borrowReserve))
+      );
+      await provider.sendAndConfirm(tx);
+
+      const reserve = await program.account.reserve.fetch(borrowReserve);
+      const state = await program.account.obligation.fetch(obligation);
+      assert.ok(reserve.liquidity.cumulativeBorrowRateWads.gt(indexBefore));
+      assert.ok(state.borrows[0].borrowedAmountWads.gt(WAD.muln(50_000_000)));
+      assert.ok(state.borrows[0].cumulativeBorrowRateWads.eq(reserve.liquidity.cumulativeBorrowRateWads));
+      // 10% of the interest is owed to the insurance fund
+      assert.ok(reserve.liquidity.accumulatedInsuranceFeesWads.gtn(0));
+    } finally {
+      await setBorrowRates(0, 0, 0);
+    }
+  });
+
+  it("rejects paused operations and halts borrowing when the circuit breaker trips", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(10_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+    const setPauses = (pauses: Partial<Record<"all" | "borrows" | "withdrawals" | "liquidations", boolean>>) =>
+      program.methods
+        .setMarketPauses({ all: false, borrows: false, withdrawals: false, liquidations: false, ...pauses })
+        .accountsPartial({ lendingMarket: lendingMarket.publicKey })
+        .rpc();
+    const setCircuitBreaker = (maxPriceMoveBps: number, windowSlots: number) =>
+      program.methods
+        .setCircuitBreaker(maxPriceMoveBps, new anchor.BN(windowSlots))
+        .accountsPartial({ lendingMarket: lendingMarket.publicKey })
+        .rpc();
+    const refresh = await refreshIxs(obligation, collateralReserve, borrowReserve);
+    const borrow = () =>
+      program.methods
+        .borrow(new anchor.BN(1_000_000))
+        .accountsPartial({ ...borrowAccounts(), obligation })
+        .preInstructions(refresh)
+        .rpc();
+    const withdraw = () =>
+      program.methods
+        .withdraw(new anchor.BN(1_000_000))
+        .accountsPartial({ ...withdrawAccounts(), obligation })
+        .preInstructions(refresh)
+        .rpc();
+
+    try {
+      await setPauses({ borrows: true });
+      await expectError(borrow(), "BorrowsPaused");
+      await withdraw();
+
+      await setPauses({ withdrawals: true });
+      await expectError(withdraw(), "WithdrawalsPaused");
+      await borrow();
+
+      await setPauses({ liquidations: true });
+      await expectError(
+        program.methods
+          .liquidate(new anchor.BN(1_000_000))
+          .accountsPartial({ ...liquidateAccounts(), obligation })
+          .preInstructions(refresh)
+          .rpc(),
+        "LiquidationsPaused"
+      );
+
+      await setPauses({ all: true });
+      await expectError(borrow(), "MarketPaused");
+      await expectError(
+        program.methods
+          .repay(new anchor.BN(1_000_000))
+          .accountsPartial({
+            obligation,
+            lendingMarket: lendingMarket.publicKey,
+            repayReserve: borrowReserve,
+            liquiditySupply: liquiditySupply(borrowReserve),
+            repayerDebt: userDebt,
+          })
+          .rpc(),
+        "MarketPaused"
+      );
+      await expectError(
+        program.methods
+          .fundReserve(new anchor.BN(1_000_000))
+          .accountsPartial({
+            lendingMarket: lendingMarket.publicKey,
+            reserve: borrowReserve,
+            liquiditySupply: liquiditySupply(borrowReserve),
+            funderLiquidity: userDebt,
+          })
+          .rpc(),
+        "MarketPaused"
+      );
+      await expectError(
+        program.methods
+          .stakeInsurance(new anchor.BN(1_000_000))
+          .accountsPartial({
+            lendingMarket: lendingMarket.publicKey,
+            insuranceFund: insuranceFund(),
+            reserve: borrowReserve,
+            insuranceVault: insuranceVault(borrowReserve),
+            insuranceStake: insuranceStake(borrowReserve),
+            stakerLiquidity: userDebt,
+          })
+          .rpc(),
+        "MarketPaused"
+      );
+    } finally {
+      await setPauses({});
+    }
+
+    // A 15% move within 100 slots passes the oracle deviation guard but trips a 10% breaker
+    await setCircuitBreaker(1_000, 100);
+    try {
+      await refreshReserve(borrowReserve).rpc();
+      await setPrice(debtOracle, ONE_USD.muln(115).divn(100));
+      // Opening a position runs its prices through the breaker too, and may not borrow on them
+      await expectError(
+        program.methods
+          .createPosition(new anchor.BN(100_000_000), new anchor.BN(10_000_000))
+          .accountsPartial(createPositionAccounts(await nextObligation()))
+          .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+          .rpc(),
+        "CircuitBreakerTripped"
+      );
+      const [tripped] = (await eventsOf(await refreshReserve(borrowReserve).rpc({ commitment: "confirmed" }))).filter(
+        (event) => event.name === "circuitBreakerTripped"
+      );
+      assert.ok(tripped.data.reserve.equals(borrowReserve));
+      assert.ok(tripped.data.referencePrice.eq(WAD));
+      assert.ok((await program.account.lendingMarket.fetch(lendingMarket.publicKey)).circuitBreaker.tripped);
+      await expectError(borrow(), "CircuitBreakerTripped");
+    } finally {
+      await setPrice(debtOracle, ONE_USD);
+      await setCircuitBreaker(0, 0);
+    }
+    await borrow();
+  });
+
+  it("changes risk parameters only through approved, timelocked proposals", async () => {
+    await expectError(
+      program.methods
+        .updateReserveConfig({ ...reserveConfig, loanToValueRatio: 60 })
+        .accountsPartial({ lendingMarket: lendingMarket.publicKey, reserve: collateralReserve })
+        .rpc(),
+      "RiskParametersGoverned"
+    );
+
+    const proposal = await proposeRiskParameters(collateralReserve, { loanToValueRatio: 60 });
+    try {
+      // One of the two signers is not enough
+      await expectError(executeRiskProposal(collateralReserve, proposal), "ProposalNotApproved");
+      await expectError(approveRiskProposal(proposal, Keypair.generate()), "Unauthorized");
+
+      await approveRiskProposal(proposal);
+      await expectError(executeRiskProposal(collateralReserve, proposal), "TimelockNotElapsed");
+
+      await waitSlots();
+      await executeRiskProposal(collateralReserve, proposal);
+      const reserve = await program.account.reserve.fetch(collateralReserve);
+      assert.equal(reserve.config.loanToValueRatio, 60);
+      await expectError(executeRiskProposal(collateralReserve, proposal), "ProposalAlreadyExecuted");
+    } finally {
+      await changeRiskParameters(collateralReserve, {});
+    }
+    assert.equal((await program.account.reserve.fetch(collateralReserve)).config.loanToValueRatio, 75);
+  });
+
+  it("rejects liquidation, borrows and withdrawals on stale state", async () => {
+    const obligation = await nextObligation();
+    await program.methods
+      .createPosition(new anchor.BN(100_000_000), new anchor.BN(10_000_000))
+      .accountsPartial(createPositionAccounts(obligation))
+      .remainingAccounts(oracleMetas(collateralReserve, borrowReserve))
+      .rpc();
+    const borrow = () =>
+      program.methods
+        .borrow(new anchor.BN(1_000_000))
+        .accountsPartial({ ...borrowAccounts(), obligation });
+
+    // Without refreshes the reserve was last updated in an earlier slot
+    await expectError(borrow().rpc(), "ReserveStale");
+    await expectError(
+      program.methods
+        .liquidate(new anchor.BN(1_000_000))
+        .accountsPartial({ ...liquidateAccounts(), obligation })
+        .rpc(),
+      "ReserveStale"
+    );
+
+    // Collateral can only be seized from a reserve other than the one repaid
+    await expectError(
+      program.methods
+        .liquidate(new anchor.BN(1_000_000))
+        .accountsPartial({ ...liquidateAccounts(), obligation, withdrawReserve: borrowReserve })
+        .rpc(),
+      "DuplicateReserve"
+    );
+
+    // Refreshing the reserves but not the obligation is not enough
+    const refresh = await refreshIxs(obligation, collateralReserve, borrowReserve);
+    await expectError(borrow().preInstructions(refresh.slice(0, 2)).rpc(), "ObligationStale");
+    await expectError(
+      program.methods
+        .withdraw(new anchor.BN(1_000_000))
+        .accountsPartial({ ...withdrawAccounts(), obligation })
+        .preInstructions(refresh.slice(0, 2))
+        .rpc(),
+      "ObligationStale"
+    );
+
+    // A refresh that skips a reserve the obligation references fails
+    await expectError(
+      borrow()
+        .preInstructions(await refreshIxs(obligation, collateralReserve))
+        .rpc(),
+      "ObligationReserveMissing"
+    );
+
+    await borrow().preInstructions(refresh).rpc();
+
+    // Interest accrued by fund_reserve in a later slot does not make the reserve fresh
+    await waitSlots();
+    const fund = await program.methods
+      .fundReserve(new anchor.BN(1))
+      .accountsPartial({
+        lendingMarket: lendingMarket.publicKey,
+        reserve: borrowReserve,
+        liquiditySupply: liquiditySupply(borrowReserve),
+        funderLiquidity: userDebt,
+      })
+      .instruction();
+    await expectError(
+      borrow()
+        .preInstructions([refresh[0], fund, refresh[2]])
+        .rpc(),
+      "ReserveStale"
+    );
   });
 });